@@ -0,0 +1,369 @@
+use std::collections::HashSet;
+
+use crate::{
+    java::{
+        ast::{
+            class::{Class, ClassType},
+            functions::{Function, FunctionKind},
+            types::{JType, TypeResolution},
+            variable::Variable,
+            JPath,
+        },
+        project::{Project, Visitor},
+    },
+    uml::{self, Pass, TypeFormat},
+};
+
+type Result = std::io::Result<()>;
+
+const TYPES: TypeFormat = TypeFormat {
+    generics: ("<", ">"),
+    arguments: ", ",
+    list: " & ",
+};
+
+/// Writes the class model as a Graphviz digraph.
+///
+/// Every class becomes a record shaped node whose id is its full class path,
+/// so ids stay the same between runs and can be matched by `gvpr` filters.
+/// Edges carry a `class` attribute of `extends`, `implements` or `nested`.
+pub struct DotGen<'a, T: std::io::Write> {
+    java: &'a Project<'a>,
+    out: &'a mut T,
+    external: HashSet<String>,
+    pass: Pass,
+    indent: &'static str,
+}
+
+impl<'a, T: std::io::Write> DotGen<'a, T> {
+    pub fn new(out: &'a mut T, java: &'a Project<'a>) -> Self {
+        Self {
+            java,
+            out,
+            external: HashSet::new(),
+            pass: Pass::Classes,
+            indent: "  ",
+        }
+    }
+
+    pub fn write(&mut self) -> Result {
+        uml::visit_passes(self.java, self)
+    }
+
+    /// Writes an edge to a supertype. Supertypes outside of the project get
+    /// a plain dashed node the first time they are referenced.
+    fn write_edge(&mut self, class: &Class, supertype: &JType, attributes: &str) -> Result {
+        let JType::Object { path, .. } = supertype else {
+            return Ok(());
+        };
+        let (id, name) = match &path.resolved {
+            TypeResolution::Generic => return Ok(()),
+            TypeResolution::Some(resolved) if self.java.omitted.contains(resolved) => return Ok(()),
+            TypeResolution::Some(resolved) => match self.java.type_map.get(resolved) {
+                Some(target) if self.java.is_hidden(target) => return Ok(()),
+                Some(target) => (target.class_path.path.clone(), None),
+                None => (resolved.path.clone(), Some(resolved.last())),
+            },
+            TypeResolution::None => (path.origional.path.clone(), Some(path.origional.last())),
+        };
+
+        if let Some(name) = name {
+            if self.external.insert(id.clone()) {
+                self.out.write_all("  ".as_bytes())?;
+                self.write_id(&id)?;
+                self.out
+                    .write_all(" [shape=box, style=dashed, label=".as_bytes())?;
+                self.write_id(name)?;
+                self.out.write_all("]\n".as_bytes())?;
+            }
+        }
+
+        self.out.write_all("  ".as_bytes())?;
+        self.write_id(&class.class_path.path)?;
+        self.out.write_all(" -> ".as_bytes())?;
+        self.write_id(&id)?;
+        self.out.write_all(" ".as_bytes())?;
+        self.out.write_all(attributes.as_bytes())?;
+        self.out.write_all("\n".as_bytes())
+    }
+
+    /// Writes a quoted DOT id.
+    fn write_id(&mut self, id: &str) -> Result {
+        self.out.write_all("\"".as_bytes())?;
+        for c in id.chars() {
+            if matches!(c, '"' | '\\') {
+                self.out.write_all("\\".as_bytes())?;
+            }
+            self.out.write_all(c.encode_utf8(&mut [0; 4]).as_bytes())?;
+        }
+        self.out.write_all("\"".as_bytes())
+    }
+
+    fn write_class(&mut self, class: &Class, indent: &str) -> Result {
+        let mut label = String::from("{");
+
+        if let Some(stereotype) = uml::stereotype(class) {
+            label.push_str(&escape_record(&format!("«{stereotype}»")));
+            label.push_str("\\n");
+        }
+
+        let name = match &class.package {
+            Some(package) => class
+                .class_path
+                .path
+                .trim_start_matches(&package.path)
+                .trim_start_matches('.'),
+            None => class.class_path.path.as_str(),
+        };
+        let mut title = name.to_owned();
+        if let Some(generics) = &class.generics {
+            title.push('<');
+            for (index, gen) in generics.definitions.iter().enumerate() {
+                title.push_str(&gen.name);
+                if index != generics.definitions.len() - 1 {
+                    title.push_str(", ");
+                }
+            }
+            title.push('>');
+        }
+        label.push_str(&escape_record(&title));
+
+        label.push('|');
+        if let ClassType::Enum(enums) = &class.class_type {
+            for name in enums {
+                label.push_str(&escape_record(name));
+                label.push_str("\\l");
+            }
+        }
+        for variable in &class.variables {
+            if !variable.meta.hidden {
+                label.push_str(&escape_record(&variable_str(class, variable)));
+                label.push_str("\\l");
+            }
+        }
+
+        label.push('|');
+        for function in &class.functions {
+            if !function.meta.hidden {
+                label.push_str(&escape_record(&function_str(class, function)));
+                label.push_str("\\l");
+            }
+        }
+        label.push('}');
+
+        self.out.write_all(indent.as_bytes())?;
+        self.write_id(&class.class_path.path)?;
+        self.out.write_all(" [label=\"".as_bytes())?;
+        self.out.write_all(label.as_bytes())?;
+        if self.java.stubs.contains(&class.class_path) {
+            self.out.write_all("\", style=dashed]\n".as_bytes())
+        } else {
+            self.out.write_all("\"]\n".as_bytes())
+        }
+    }
+}
+
+impl<'a, T: std::io::Write> uml::PassVisitor for DotGen<'a, T> {
+    fn set_pass(&mut self, pass: Pass) {
+        self.pass = pass;
+    }
+}
+
+impl<'a, T: std::io::Write> Visitor for DotGen<'a, T> {
+    type Ok = ();
+    type Err = std::io::Error;
+
+    fn visit_start(&mut self) -> Result {
+        if self.pass != Pass::Classes {
+            return Ok(());
+        }
+        self.out.write_all(
+            "digraph uml {
+  rankdir=BT
+  node [shape=record, fontname=\"Helvetica\", fontsize=10]
+  edge [fontname=\"Helvetica\", fontsize=9]\n\n"
+                .as_bytes(),
+        )
+    }
+
+    fn visit_package_start(&mut self, package: &JPath) -> Result {
+        if self.pass != Pass::Classes || package.path.is_empty() {
+            self.indent = "  ";
+            return Ok(());
+        }
+        self.indent = "    ";
+        self.out.write_all("  subgraph ".as_bytes())?;
+        self.write_id(&format!("cluster_{}", package.path))?;
+        self.out.write_all(" {\n    label=".as_bytes())?;
+        self.write_id(&package.path)?;
+        self.out.write_all("\n".as_bytes())
+    }
+
+    fn visit_class(&mut self, class: &Class) -> Result {
+        if self.java.is_hidden(class) {
+            return Ok(());
+        }
+        match self.pass {
+            Pass::Classes => self.write_class(class, self.indent),
+            Pass::Relations => {
+                for extends in class.extends.iter().flatten() {
+                    self.write_edge(class, extends, "[class=\"extends\", arrowhead=empty]")?;
+                }
+                for implements in class.implements.iter().flatten() {
+                    self.write_edge(
+                        class,
+                        implements,
+                        "[class=\"implements\", arrowhead=empty, style=dashed]",
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn visit_nested_class(&mut self, parent: &Class, nested: &Class) -> Result {
+        if self.pass != Pass::Relations || self.java.is_hidden(nested) {
+            return Ok(());
+        }
+        self.out.write_all("  ".as_bytes())?;
+        self.write_id(&parent.class_path.path)?;
+        self.out.write_all(" -> ".as_bytes())?;
+        self.write_id(&nested.class_path.path)?;
+        self.out
+            .write_all(" [class=\"nested\", dir=back, arrowtail=odot, arrowhead=none]\n".as_bytes())
+    }
+
+    fn visit_package_end(&mut self, package: &JPath) -> Result {
+        if self.pass != Pass::Classes || package.path.is_empty() {
+            return Ok(());
+        }
+        self.out.write_all("  }\n".as_bytes())
+    }
+
+    fn visit_end(&mut self) -> Result {
+        match self.pass {
+            Pass::Classes => self.out.write_all("\n".as_bytes()),
+            Pass::Relations => self.out.write_all("}\n".as_bytes()),
+        }
+    }
+}
+
+/// Escapes the characters that have a meaning inside a record label.
+fn escape_record(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for c in str.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn variable_str(class: &Class, variable: &Variable) -> String {
+    let mut str = String::new();
+    str.push_str(uml::visibility(
+        class.member_visibility(variable.visibility),
+    ));
+    if variable.modifiers.m_static() {
+        str.push_str("static ");
+    }
+    str.push_str(variable.name);
+    str.push_str(" : ");
+    TYPES.write(&variable.jtype, &uml::written_name, &mut str);
+    str
+}
+
+fn function_str(class: &Class, function: &Function) -> String {
+    let mut str = String::new();
+    str.push_str(uml::visibility(
+        class.member_visibility(function.visibility),
+    ));
+    if function.modifiers.m_static() {
+        str.push_str("static ");
+    }
+    if function.modifiers.m_abstract() {
+        str.push_str("abstract ");
+    }
+    str.push_str(function.name);
+    str.push('(');
+    let parameters: Vec<_> = function
+        .parameters
+        .iter()
+        .filter(|param| !param.meta.hidden)
+        .collect();
+    for (index, param) in parameters.iter().enumerate() {
+        str.push_str(param.name);
+        str.push_str(" : ");
+        TYPES.write(&param.jtype, &uml::written_name, &mut str);
+        if param.vargs {
+            str.push_str("...");
+        }
+        if index != parameters.len() - 1 {
+            str.push_str(", ");
+        }
+    }
+    str.push(')');
+    if let FunctionKind::Regular(ret) | FunctionKind::AnnotationElement(ret, _) = &function.kind {
+        str.push_str(" : ");
+        TYPES.write(ret, &uml::written_name, &mut str);
+    }
+    str
+}
+
+#[test]
+fn test_dot() {
+    let files = [
+        (
+            "shapes/Shape.java",
+            r#"
+        package shapes;
+
+        public abstract class Shape<T> implements Comparable {
+            protected static int count;
+            public abstract double area();
+        }
+        "#,
+        ),
+        (
+            "shapes/Circle.java",
+            r#"
+        package shapes;
+
+        import java.util.Map;
+
+        public class Circle extends Shape<Circle> {
+            private Map<String, int[]> labels;
+            public static Circle unit() { return null; }
+            enum Kind { SMALL, LARGE }
+        }
+        "#,
+        ),
+    ];
+
+    let out = crate::render(&files, |out, project| DotGen::new(out, project).write());
+
+    assert!(out.starts_with("digraph uml {\n"));
+    assert!(out.contains("  subgraph \"cluster_shapes\" {\n    label=\"shapes\"\n"));
+    assert!(out.contains(
+        "    \"shapes.Shape\" [label=\"{«abstract»\\nShape\\<T\\>|#static count : int\\l|+abstract area() : double\\l}\"]\n"
+    ));
+    assert!(out.contains(
+        "    \"shapes.Circle\" [label=\"{Circle|-labels : Map\\<String, int[]\\>\\l|+static unit() : Circle\\l}\"]\n"
+    ));
+    assert!(out.contains(
+        "    \"shapes.Circle.Kind\" [label=\"{«enumeration»\\nCircle.Kind|SMALL\\lLARGE\\l|}\"]\n"
+    ));
+    assert!(out.contains(
+        "  \"shapes.Circle\" -> \"shapes.Shape\" [class=\"extends\", arrowhead=empty]\n"
+    ));
+    assert!(out.contains(
+        "  \"shapes.Shape\" -> \"java.lang.Comparable\" [class=\"implements\", arrowhead=empty, style=dashed]\n"
+    ));
+    assert!(out.contains(
+        "  \"shapes.Circle\" -> \"shapes.Circle.Kind\" [class=\"nested\", dir=back, arrowtail=odot, arrowhead=none]\n"
+    ));
+    assert!(out
+        .contains("  \"java.lang.Comparable\" [shape=box, style=dashed, label=\"Comparable\"]\n"));
+    assert!(out.ends_with("}\n"));
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use bitfield_struct::bitfield;
 
-use super::tokenizer::UmlMeta;
+use super::{javadoc::Javadoc, tokenizer::UmlMeta};
 
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
 pub struct JPath {
@@ -50,7 +50,7 @@ impl JPath {
     }
 
     pub fn last(&self) -> &str {
-        self.path.split('.').last().unwrap()
+        self.path.split('.').next_back().unwrap()
     }
 
     pub fn first(&self) -> &str {
@@ -60,7 +60,7 @@ impl JPath {
 
 impl std::fmt::Display for JPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut iter = self.path.split(|c| matches!(c, 'C' | 'B'));
+        let mut iter = self.path.split(['C', 'B']);
         if let Some(part) = iter.next() {
             write!(f, "{}", part)?;
         }
@@ -79,6 +79,22 @@ pub enum Visibility {
     None,
 }
 
+impl Visibility {
+    /// Whether something with this visibility is at least as accessible as
+    /// `min`, package private sitting between protected and private.
+    pub fn is_at_least(self, min: Visibility) -> bool {
+        fn rank(vis: Visibility) -> u8 {
+            match vis {
+                Visibility::Public => 3,
+                Visibility::Protected => 2,
+                Visibility::None => 1,
+                Visibility::Private => 0,
+            }
+        }
+        rank(self) >= rank(min)
+    }
+}
+
 #[bitfield(u16, debug = false)]
 pub struct Modifiers {
     pub m_static: bool,
@@ -126,6 +142,8 @@ impl std::fmt::Debug for Modifiers {
 pub struct Metadata<'a> {
     pub hidden: bool,
     pub other: Vec<UmlMeta<'a>>,
+    /// The `/** ... */` comment in front of the declaration
+    pub doc: Option<Javadoc>,
 }
 
 impl<'a> Metadata<'a> {
@@ -143,8 +161,20 @@ impl<'a> Metadata<'a> {
 
 #[derive(Debug, Default, Clone)]
 pub struct Imports {
+    /// `import a.B;` keyed by the imported type's name
     pub name_map: HashMap<String, Import>,
+    /// `import a.*;`
     pub wildcard: Vec<Import>,
+    /// The other top level types of the file's package, filled in by
+    /// `Project::resolve_imports`.
+    pub package_map: HashMap<String, Import>,
+    /// The types named by the `wildcard` imports, filled in by
+    /// `Project::resolve_imports`.
+    pub wildcard_map: HashMap<String, Import>,
+    /// `import static a.B.member;` keyed by the imported member's name
+    pub static_name_map: HashMap<String, Import>,
+    /// `import static a.B.*;`
+    pub static_wildcard: Vec<Import>,
 }
 impl Imports {
     pub fn new() -> Self {
@@ -152,12 +182,16 @@ impl Imports {
     }
 
     pub fn add(&mut self, import: Import) {
-        if !import.is_static {
-            if import.path.is_wildcard() {
-                self.wildcard.push(import);
-            } else {
+        match (import.is_static, import.path.is_wildcard()) {
+            (false, true) => self.wildcard.push(import),
+            (false, false) => {
                 self.name_map.insert(import.path.last().to_owned(), import);
             }
+            (true, true) => self.static_wildcard.push(import),
+            (true, false) => {
+                self.static_name_map
+                    .insert(import.path.last().to_owned(), import);
+            }
         }
     }
 }
@@ -175,12 +209,112 @@ impl Import {
 
 #[derive(Debug, Default, Clone)]
 pub struct Annotations {
-    pub annotations: Vec<String>,
+    pub annotations: Vec<Annotation>,
 }
 impl Annotations {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Finds an annotation by its name, which matches both `@Name` and
+    /// `@some.pkg.Name` when given without a package.
+    pub fn get(&self, name: &str) -> Option<&Annotation> {
+        self.annotations
+            .iter()
+            .find(|annotation| annotation.name.path == name || annotation.name.last() == name)
+    }
+}
+
+/// An annotation as written, `@Name(element = value, ...)`.
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub name: JPath,
+    /// The element-value pairs in the order they were written. The lone
+    /// value of `@Name(value)` belongs to the `value` element.
+    pub elements: Vec<(String, ElementValue)>,
+}
+impl Annotation {
+    pub fn element(&self, name: &str) -> Option<&ElementValue> {
+        self.elements
+            .iter()
+            .find(|(element, _)| element == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn value(&self) -> Option<&ElementValue> {
+        self.element("value")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ElementValue {
+    /// A string, char, numeric, boolean or `null` literal as written, with
+    /// its quotes and any leading `-`
+    Literal(String),
+    /// `Name.class`
+    Class(types::JType),
+    /// A dotted name like `ElementType.FIELD`, usually an enum constant but
+    /// any constant can be referred to this way
+    EnumConstant(JPath),
+    Annotation(Box<Annotation>),
+    /// `{a, b}`, a lone value is not wrapped in an array
+    Array(Vec<ElementValue>),
+    /// Any other constant expression, as written
+    Expression(String),
+}
+impl ElementValue {
+    /// The contents of a string literal with its escape sequences replaced.
+    /// Text blocks and every other kind of value give `None`.
+    pub fn string(&self) -> Option<String> {
+        let ElementValue::Literal(literal) = self else {
+            return None;
+        };
+        let contents = literal.strip_prefix('"')?.strip_suffix('"')?;
+        if contents.starts_with("\"\"") {
+            return None;
+        }
+
+        let mut string = String::with_capacity(contents.len());
+        let mut chars = contents.chars();
+        while let Some(char) = chars.next() {
+            if char != '\\' {
+                string.push(char);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some('r') => string.push('\r'),
+                Some('b') => string.push('\u{8}'),
+                Some('f') => string.push('\u{c}'),
+                Some('s') => string.push(' '),
+                Some('u') => {
+                    let rest = chars.as_str().trim_start_matches('u');
+                    let code = rest
+                        .get(..4)
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+                    string.push(code.and_then(char::from_u32).unwrap_or('\u{fffd}'));
+                    chars = rest.get(4..).unwrap_or("").chars();
+                }
+                Some(first @ '0'..='7') => {
+                    let max = if first <= '3' { 3 } else { 2 };
+                    let rest = chars.as_str();
+                    let len = 1 + rest
+                        .chars()
+                        .take(max - 1)
+                        .take_while(|c| ('0'..='7').contains(c))
+                        .count();
+                    let digits = &contents[contents.len() - rest.len() - 1..][..len];
+                    let code = u32::from_str_radix(digits, 8).unwrap_or(0);
+                    string.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    chars = rest[len - 1..].chars();
+                }
+                Some(other) => string.push(other),
+                None => {}
+            }
+        }
+        Some(string)
+    }
 }
 
 pub mod class {
@@ -191,7 +325,7 @@ pub mod class {
 
     use super::{
         functions::Function, generics::GenericDefinition, types::JType, variable::Variable,
-        Annotations, Imports, JPath, Metadata, Modifiers, Visibility,
+        Annotations, ElementValue, Imports, JPath, Metadata, Modifiers, Visibility,
     };
 
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -224,6 +358,99 @@ pub mod class {
         pub functions: Vec<Function<'a>>,
         pub inner_classes: Vec<Class<'a>>,
     }
+
+    impl<'a> Class<'a> {
+        /// The visibility a member declared with `declared` actually has.
+        /// Members of interfaces and annotation types without a modifier
+        /// are implicitly public.
+        pub fn member_visibility(&self, declared: Visibility) -> Visibility {
+            match (&self.class_type, declared) {
+                (ClassType::Interface | ClassType::InterfaceA, Visibility::None) => {
+                    Visibility::Public
+                }
+                _ => declared,
+            }
+        }
+
+        /// The policy given by the `@Retention` of an annotation type. Java
+        /// uses [`RetentionPolicy::Class`] when there is none.
+        pub fn retention(&self) -> Option<RetentionPolicy> {
+            let ElementValue::EnumConstant(policy) = self.annotations.get("Retention")?.value()?
+            else {
+                return None;
+            };
+            match policy.last() {
+                "SOURCE" => Some(RetentionPolicy::Source),
+                "CLASS" => Some(RetentionPolicy::Class),
+                "RUNTIME" => Some(RetentionPolicy::Runtime),
+                _ => None,
+            }
+        }
+
+        /// The kinds of declarations listed by the `@Target` of an
+        /// annotation type, `None` when it has none. Names that aren't an
+        /// `ElementType` constant are left out.
+        pub fn targets(&self) -> Option<Vec<ElementType>> {
+            let targets = match self.annotations.get("Target")?.value()? {
+                ElementValue::Array(targets) => targets.as_slice(),
+                target => std::slice::from_ref(target),
+            };
+            Some(
+                targets
+                    .iter()
+                    .filter_map(|target| match target {
+                        ElementValue::EnumConstant(target) => ElementType::from_name(target.last()),
+                        _ => None,
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    /// `java.lang.annotation.RetentionPolicy`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RetentionPolicy {
+        Source,
+        Class,
+        Runtime,
+    }
+
+    /// `java.lang.annotation.ElementType`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ElementType {
+        Type,
+        Field,
+        Method,
+        Parameter,
+        Constructor,
+        LocalVariable,
+        AnnotationType,
+        Package,
+        TypeParameter,
+        TypeUse,
+        Module,
+        RecordComponent,
+    }
+
+    impl ElementType {
+        pub fn from_name(name: &str) -> Option<Self> {
+            Some(match name {
+                "TYPE" => ElementType::Type,
+                "FIELD" => ElementType::Field,
+                "METHOD" => ElementType::Method,
+                "PARAMETER" => ElementType::Parameter,
+                "CONSTRUCTOR" => ElementType::Constructor,
+                "LOCAL_VARIABLE" => ElementType::LocalVariable,
+                "ANNOTATION_TYPE" => ElementType::AnnotationType,
+                "PACKAGE" => ElementType::Package,
+                "TYPE_PARAMETER" => ElementType::TypeParameter,
+                "TYPE_USE" => ElementType::TypeUse,
+                "MODULE" => ElementType::Module,
+                "RECORD_COMPONENT" => ElementType::RecordComponent,
+                _ => return None,
+            })
+        }
+    }
 }
 
 pub mod variable {
@@ -244,7 +471,8 @@ pub mod variable {
 pub mod functions {
 
     use super::{
-        generics::GenericDefinition, types::JType, Annotations, Metadata, Modifiers, Visibility,
+        generics::GenericDefinition, types::JType, Annotations, ElementValue, Metadata, Modifiers,
+        Visibility,
     };
 
     #[derive(Debug, Clone)]
@@ -252,6 +480,9 @@ pub mod functions {
         Regular(JType),
         Constructor,
         CompactConstructor,
+        /// An element of an annotation type, with its type and its
+        /// `default` value
+        AnnotationElement(JType, Option<ElementValue>),
     }
 
     #[derive(Debug, Clone)]
@@ -279,7 +510,7 @@ pub mod functions {
 }
 
 pub mod types {
-    use std::num::NonZeroU8;
+    use std::{num::NonZeroU8, ops::Range};
 
     use super::{generics::GenericInvoction, JPath};
 
@@ -296,6 +527,39 @@ pub mod types {
         Boolean,
     }
 
+    impl Primitive {
+        /// The primitive type spelled `name`, `void` included.
+        pub fn from_name(name: &str) -> Option<Self> {
+            Some(match name {
+                "void" => Primitive::Void,
+                "boolean" => Primitive::Boolean,
+                "byte" => Primitive::Byte,
+                "char" => Primitive::Char,
+                "double" => Primitive::Double,
+                "float" => Primitive::Float,
+                "int" => Primitive::Int,
+                "long" => Primitive::Long,
+                "short" => Primitive::Short,
+                _ => return None,
+            })
+        }
+
+        /// The keyword naming the type.
+        pub fn name(&self) -> &'static str {
+            match self {
+                Primitive::Byte => "byte",
+                Primitive::Short => "short",
+                Primitive::Int => "int",
+                Primitive::Long => "long",
+                Primitive::Float => "float",
+                Primitive::Double => "double",
+                Primitive::Char => "char",
+                Primitive::Void => "void",
+                Primitive::Boolean => "boolean",
+            }
+        }
+    }
+
     #[derive(Debug, Clone)]
     pub enum TypeResolution {
         None,
@@ -307,13 +571,16 @@ pub mod types {
     pub struct TypePath {
         pub origional: JPath,
         pub resolved: TypeResolution,
+        /// Where the path was written in its source file.
+        pub range: Range<usize>,
     }
 
     impl TypePath {
-        pub fn new(origional: JPath) -> Self {
+        pub fn new(origional: JPath, range: Range<usize>) -> Self {
             Self {
                 origional,
                 resolved: TypeResolution::None,
+                range,
             }
         }
     }
@@ -0,0 +1,158 @@
+/// A block tag of a documentation comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JavadocTag {
+    /// `@param name description`, where `name` is `<T>` for type parameters
+    Param { name: String, description: String },
+    /// `@return description`
+    Return(String),
+    /// `@throws` or its older spelling `@exception`
+    Throws {
+        exception: String,
+        description: String,
+    },
+    /// `@see reference`
+    See(String),
+    /// `@deprecated description`
+    Deprecated(String),
+    /// `@since version`
+    Since(String),
+    /// Any other block tag, like `@author`, named without its `@`
+    Other { name: String, text: String },
+}
+
+/// A parsed `/** ... */` comment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Javadoc {
+    /// The first sentence of the description as plain text, with inline
+    /// tags like `{@code ...}` replaced by their text.
+    pub summary: String,
+    /// The main description as written, without the leading `*` of every
+    /// line.
+    pub description: String,
+    pub tags: Vec<JavadocTag>,
+}
+
+impl Javadoc {
+    /// Parses a whole comment, including its `/**` and `*/`.
+    pub fn parse(comment: &str) -> Self {
+        let body = comment.strip_prefix("/**").unwrap_or(comment);
+        let body = body.strip_suffix("*/").unwrap_or(body);
+
+        let mut description = String::new();
+        let mut tags: Vec<(&str, String)> = Vec::new();
+        for line in body.lines() {
+            let line = line.trim_start().trim_start_matches('*');
+            let line = line.strip_prefix(' ').unwrap_or(line).trim_end();
+            if let Some(tag) = line.strip_prefix('@') {
+                let (name, text) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+                tags.push((name, text.trim().to_owned()));
+            } else if let Some((_, text)) = tags.last_mut() {
+                if !line.trim().is_empty() {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push_str(line.trim());
+                }
+            } else {
+                description.push_str(line);
+                description.push('\n');
+            }
+        }
+        let description = description.trim().to_owned();
+
+        let plain = plain_text(&description);
+        let summary = match plain
+            .char_indices()
+            .find(|&(i, c)| c == '.' && plain[i + 1..].starts_with(char::is_whitespace))
+        {
+            Some((i, _)) => &plain[..=i],
+            None => &plain,
+        };
+        let summary = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let tags = tags
+            .into_iter()
+            .map(|(name, text)| {
+                let split = |text: String| match text.split_once(char::is_whitespace) {
+                    Some((first, rest)) => (first.to_owned(), rest.trim().to_owned()),
+                    None => (text, String::new()),
+                };
+                match name {
+                    "param" => {
+                        let (name, description) = split(text);
+                        JavadocTag::Param { name, description }
+                    }
+                    "return" => JavadocTag::Return(text),
+                    "throws" | "exception" => {
+                        let (exception, description) = split(text);
+                        JavadocTag::Throws {
+                            exception,
+                            description,
+                        }
+                    }
+                    "see" => JavadocTag::See(text),
+                    "deprecated" => JavadocTag::Deprecated(text),
+                    "since" => JavadocTag::Since(text),
+                    name => JavadocTag::Other {
+                        name: name.to_owned(),
+                        text,
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            summary,
+            description,
+            tags,
+        }
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.tags
+            .iter()
+            .any(|tag| matches!(tag, JavadocTag::Deprecated(_)))
+    }
+}
+
+/// Replaces the inline tags in `text` with the text they would render as.
+/// `{@link Ref label}` becomes its label, or the reference when it has none.
+fn plain_text(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{@") {
+        out.push_str(&rest[..start]);
+        let tag = &rest[start + "{@".len()..];
+
+        // inline tags like `{@code}` may hold balanced braces of their own
+        let mut depth = 1usize;
+        let end = tag.char_indices().find(|&(_, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        });
+        let Some((end, _)) = end else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+
+        let (name, content) = tag[..end]
+            .split_once(char::is_whitespace)
+            .unwrap_or((&tag[..end], ""));
+        let content = content.trim();
+        match name {
+            "inheritDoc" => {}
+            "link" | "linkplain" => match content.split_once(char::is_whitespace) {
+                Some((_, label)) => out.push_str(label.trim()),
+                None => out.push_str(content.trim_start_matches('#').replace('#', ".").as_str()),
+            },
+            _ => out.push_str(content),
+        }
+        rest = &tag[end + '}'.len_utf8()..];
+    }
+    out.push_str(rest);
+    out
+}
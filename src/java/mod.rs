@@ -1,7 +1,10 @@
 pub mod ast;
 pub mod code_gen;
+pub mod javadoc;
 pub mod parser;
+pub mod prelude;
 pub mod project;
+pub mod source;
 pub mod tokenizer;
 
 #[test]
@@ -113,19 +116,12 @@ fn test() {
     }
     "#;
 
-    match parser::Parser::new(TEST).parse() {
-        Ok(ok) => {
-            println!("{:#?}", ok);
-        }
-        Err(err) => {
-            println!("{:#?}", err);
-            match err {
-                parser::ParseError::ExpectedToken { range, .. }
-                | parser::ParseError::UnexpectedToken { range, .. } => {
-                    println!("{}", &TEST[..range.start]);
-                }
-                _ => {}
-            }
+    let result = parser::Parser::new(TEST).parse();
+    println!("{:#?}", result.classes);
+    for err in result.errors {
+        println!("{:#?}", err);
+        if let Some(range) = err.range {
+            println!("{}", &TEST[..range.start]);
         }
     }
 }
@@ -134,9 +130,1020 @@ pub fn test_project() {
     let mut files = project::Files::new();
     files.load_dir("./test_java/p1").unwrap();
 
-    let mut project = project::Project::parse_all(&files).unwrap();
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
 
     project.resolve_imports();
     project.resolve_types();
     println!("{:#?}", project);
 }
+
+#[test]
+fn test_load_dir() {
+    let root = std::env::temp_dir().join(format!("java_uml_load_{}", std::process::id()));
+    let res = root.join("res");
+    std::fs::create_dir_all(&res).unwrap();
+    std::fs::write(root.join("A.java"), "class A {}").unwrap();
+    std::fs::write(root.join("package-info.java"), "@Deprecated package a;").unwrap();
+    std::fs::write(root.join("module-info.java"), "module a {}").unwrap();
+    std::fs::write(res.join("img.png"), [0x89, 0xff, 0xfe]).unwrap();
+
+    let mut files = project::Files::new();
+    files.load_dir(&root).unwrap();
+    let loaded: Vec<_> = files.files.keys().collect();
+    assert_eq!(loaded, [&root.join("A.java")]);
+
+    std::fs::write(res.join("B.java"), [0x89, 0xff, 0xfe]).unwrap();
+    let err = project::Files::new().load_dir(&root).unwrap_err();
+    std::fs::remove_dir_all(&root).unwrap();
+    assert_eq!(err.path, res.join("B.java"));
+    assert!(err.to_string().contains("B.java"));
+}
+
+#[test]
+fn test_recovery() {
+    static TEST: &str = r#"
+    public class Bad {
+        int ok1;
+        void broken(int a b) { int x = 1; }
+        int ok2;
+        class Inner {
+            int x y;
+            int z;
+        }
+        Runnable r = () -> { a(); b(); };
+        int ok3;
+    "#;
+
+    let result = parser::Parser::new(TEST).parse();
+    assert_eq!(result.errors.len(), 3);
+
+    let class = &result.classes[0];
+    let names: Vec<_> = class.variables.iter().map(|v| v.name).collect();
+    assert_eq!(names, ["ok1", "ok2", "r", "ok3"]);
+    assert!(class.functions.is_empty());
+    assert_eq!(class.inner_classes[0].variables[0].name, "z");
+}
+
+#[test]
+fn test_recovery_at_error_token() {
+    // the `;` that caused the error ends the member
+    let result = parser::Parser::new("class A { int ; int a; int b; }").parse();
+    assert_eq!(result.errors.len(), 1);
+    let names: Vec<_> = result.classes[0].variables.iter().map(|v| v.name).collect();
+    assert_eq!(names, ["a", "b"]);
+
+    // and the `{` that caused it opens a block to skip
+    let result =
+        parser::Parser::new("class A { void f(int { x(); } int a; int b; } class B { int c; }")
+            .parse();
+    assert_eq!(result.errors.len(), 1);
+    let names: Vec<_> = result.classes[0].variables.iter().map(|v| v.name).collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(result.classes[1].variables[0].name, "c");
+}
+
+#[test]
+fn test_multiple_top_level() {
+    static TEST: &str = r#"
+    package p;
+
+    import java.util.List;
+
+    public class Main { List<Helper> helpers; }
+    class Helper {}
+    interface Thing {}
+    enum Kind { A, B }
+    record Pair(int a, int b) {}
+    @interface Marker {}
+    "#;
+
+    let result = parser::Parser::new(TEST).parse();
+    assert!(result.errors.is_empty());
+
+    let names: Vec<_> = result.classes.iter().map(|c| c.name).collect();
+    assert_eq!(names, ["Main", "Helper", "Thing", "Kind", "Pair", "Marker"]);
+    assert!(result
+        .classes
+        .iter()
+        .all(|c| std::sync::Arc::ptr_eq(&c.imports, &result.classes[0].imports)));
+}
+
+/// What `jtype` resolved to, `<generic>` for type parameters and an empty
+/// string for primitives and types that didn't resolve.
+#[cfg(test)]
+fn resolved_name(jtype: &ast::types::JType) -> String {
+    use ast::types::{JType, TypeResolution};
+
+    match jtype {
+        JType::Object { path, .. } => match &path.resolved {
+            TypeResolution::Some(path) => path.path.clone(),
+            TypeResolution::Generic => "<generic>".into(),
+            TypeResolution::None => "".into(),
+        },
+        _ => "".into(),
+    }
+}
+
+/// [`resolved_name`] of the type of every field of `class`.
+#[cfg(test)]
+fn resolved_names(project: &project::Project, class: &str) -> Vec<String> {
+    project.type_map[&ast::JPath { path: class.into() }]
+        .variables
+        .iter()
+        .map(|v| resolved_name(&v.jtype))
+        .collect()
+}
+
+#[test]
+fn test_prelude() {
+    let mut files = project::Files::new();
+    files.files.insert(
+        "p/Main.java".into(),
+        "package p; public class Main { String name; Exception error; Runnable task; }".into(),
+    );
+    files.files.insert(
+        "p/Exception.java".into(),
+        "package p; public class Exception {}".into(),
+    );
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    assert_eq!(
+        resolved_names(&project, "p.Main"),
+        ["java.lang.String", "p.Exception", "java.lang.Runnable"]
+    );
+}
+
+#[test]
+fn test_static_imports() {
+    let mut files = project::Files::new();
+    files.files.insert(
+        "p/Outer.java".into(),
+        "package p; public class Outer { public static class Entry {} public static class Node {} public static int MAX; public static Object make() { return null; } }".into(),
+    );
+    files.files.insert(
+        "q/User.java".into(),
+        "package q; import static p.Outer.Entry; import static p.Outer.MAX; import static p.Outer.make; import static p.Outer.*; public class User { Entry entry; Node node; MAX max; make made; } class Other {}".into(),
+    );
+    // a single static import shadows the types of the package
+    files.files.insert(
+        "q/Entry.java".into(),
+        "package q; public class Entry {}".into(),
+    );
+    // unless it imports a field or method
+    files
+        .files
+        .insert("q/MAX.java".into(), "package q; public class MAX {}".into());
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_imports();
+    project.resolve_types();
+
+    assert_eq!(
+        resolved_names(&project, "q.User"),
+        ["p.Outer.Entry", "p.Outer.Node", "q.MAX", ""]
+    );
+
+    // once per file however many classes it declares and runs there are
+    let imports: Vec<_> = project
+        .static_imports
+        .iter()
+        .map(|import| {
+            (
+                import.file.to_str().unwrap(),
+                import.owner.path.as_str(),
+                import.member.as_deref(),
+                import.is_type,
+            )
+        })
+        .collect();
+    assert_eq!(
+        imports,
+        [
+            ("q/User.java", "p.Outer", None, false),
+            ("q/User.java", "p.Outer", Some("Entry"), true),
+            ("q/User.java", "p.Outer", Some("MAX"), false),
+            ("q/User.java", "p.Outer", Some("make"), false)
+        ]
+    );
+}
+
+#[test]
+fn test_method_generics() {
+    use ast::functions::FunctionKind;
+
+    let mut files = project::Files::new();
+    files
+        .files
+        .insert("p/T.java".into(), "package p; public class T {}".into());
+    files.files.insert(
+        "p/Util.java".into(),
+        r#"
+        package p;
+        import java.util.List;
+        public class Util {
+            <E> Util(E first, T last) {}
+            static <T extends Comparable<T>> T max(List<T> list) throws T { return null; }
+            T other() { return null; }
+        }
+        "#
+        .into(),
+    );
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    let util = &project.type_map[&ast::JPath {
+        path: "p.Util".into(),
+    }];
+
+    let constructor = &util.functions[0];
+    assert_eq!(resolved_name(&constructor.parameters[0].jtype), "<generic>");
+    assert_eq!(resolved_name(&constructor.parameters[1].jtype), "p.T");
+
+    let max = &util.functions[1];
+    let FunctionKind::Regular(ret) = &max.kind else {
+        panic!()
+    };
+    assert_eq!(resolved_name(ret), "<generic>");
+    assert_eq!(resolved_name(&max.throws.as_ref().unwrap()[0]), "<generic>");
+    let bound = &max.generics.as_ref().unwrap().definitions[0]
+        .extend_bound
+        .as_ref()
+        .unwrap()[0];
+    assert_eq!(resolved_name(bound), "java.lang.Comparable");
+
+    let FunctionKind::Regular(ret) = &util.functions[2].kind else {
+        panic!()
+    };
+    assert_eq!(resolved_name(ret), "p.T");
+}
+
+#[test]
+fn test_member_types() {
+    let mut files = project::Files::new();
+    files.files.insert(
+        "p/Node.java".into(),
+        "package p; public class Node {}".into(),
+    );
+    files.files.insert(
+        "p/Base.java".into(),
+        r#"
+        package p;
+        public interface Base {
+            class Inner { class Deeper {} }
+        }
+        "#
+        .into(),
+    );
+    files.files.insert(
+        "p/Tree.java".into(),
+        r#"
+        package p;
+        import java.util.AbstractMap;
+        public class Tree extends AbstractMap implements Base {
+            Node root;
+            Inner inner;
+            Inner.Deeper deeper;
+            Base.Inner.Deeper qualified;
+            Entry entry;
+            static class Node {
+                Node left;
+                Inner inner;
+            }
+        }
+        "#
+        .into(),
+    );
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    assert_eq!(
+        resolved_names(&project, "p.Tree"),
+        [
+            "p.Tree.Node",
+            "p.Base.Inner",
+            "p.Base.Inner.Deeper",
+            "p.Base.Inner.Deeper",
+            "java.util.Map.Entry"
+        ]
+    );
+    assert_eq!(
+        resolved_names(&project, "p.Tree.Node"),
+        ["p.Tree.Node", "p.Base.Inner"]
+    );
+
+    // a member type inherited through several levels of project classes
+    let mut files = project::Files::new();
+    files.files.insert(
+        "p/Root.java".into(),
+        "package p; public interface Root { class Leaf {} }".into(),
+    );
+    files.files.insert(
+        "p/A.java".into(),
+        "package p; public class A implements Root {}".into(),
+    );
+    files.files.insert(
+        "p/B.java".into(),
+        "package p; public class B extends A {}".into(),
+    );
+    files.files.insert(
+        "p/C.java".into(),
+        "package p; public class C extends B { Leaf leaf; }".into(),
+    );
+    files.files.insert(
+        "p/D.java".into(),
+        "package p; public class D extends C { Leaf leaf; }".into(),
+    );
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    assert_eq!(resolved_names(&project, "p.C"), ["p.Root.Leaf"]);
+    assert_eq!(resolved_names(&project, "p.D"), ["p.Root.Leaf"]);
+}
+
+#[test]
+fn test_unresolved_types() {
+    use project::TypeUsage;
+
+    let mut files = project::Files::new();
+    files.files.insert(
+        "p/A.java".into(),
+        "package p;\nimport java.util.List;\npublic class A extends Missing {\n    List<Foo> foos;\n    Foo one(Bar b) { return null; }\n    java.util.function.Function<String, java.util.Map.Entry<Bar, ?>> f;\n}\n"
+            .into(),
+    );
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    let report = project.unresolved_types();
+    let names: Vec<_> = report.by_name.keys().map(String::as_str).collect();
+    assert_eq!(names, ["Bar", "Foo", "Missing"]);
+
+    let foo: Vec<_> = report.by_name["Foo"]
+        .iter()
+        .map(|r| (r.line, r.column, r.usage, r.member))
+        .collect();
+    assert_eq!(
+        foo,
+        [
+            (4, 10, TypeUsage::Field, Some("foos")),
+            (5, 5, TypeUsage::ReturnType, Some("one"))
+        ]
+    );
+    assert_eq!(report.by_name["Missing"][0].usage, TypeUsage::Extends);
+    assert!(report.on_demand.is_empty());
+    // a qualified name starting with a package outside of the project
+    // can't be checked and isn't reported
+    assert_eq!(report.by_name["Bar"].len(), 2);
+    assert_eq!(
+        resolved_names(&project, "p.A")[1],
+        "java.util.function.Function"
+    );
+
+    // names an on-demand import from outside the project could declare are
+    // set apart, on-demand imports of project packages can be checked
+    files.files.insert(
+        "p/A.java".into(),
+        "package p;\nimport java.util.*;\nimport q.*;\npublic class A extends Missing {\n    List<Foo> foos;\n    q.Gone gone;\n}\n"
+            .into(),
+    );
+    files
+        .files
+        .insert("q/B.java".into(), "package q; public class B {}".into());
+    files.files.insert(
+        "p/C.java".into(),
+        "package p; import q.*; class C { Nope nope; }".into(),
+    );
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    let report = project.unresolved_types();
+    let names: Vec<_> = report.by_name.keys().map(String::as_str).collect();
+    assert_eq!(names, ["Gone", "Nope"]);
+    let names: Vec<_> = report.on_demand.keys().map(String::as_str).collect();
+    assert_eq!(names, ["Foo", "List", "Missing"]);
+    assert_eq!(
+        report.on_demand["List"][0].on_demand,
+        [ast::JPath {
+            path: "java.util".into()
+        }]
+    );
+}
+
+#[test]
+fn test_duplicates() {
+    use project::DuplicatePolicy;
+
+    let mut files = project::Files::new();
+    files.files.insert(
+        "a/A.java".into(),
+        "package x; class A { int first; static class Nested {} }".into(),
+    );
+    files
+        .files
+        .insert("b/A.java".into(), "package x; class A { int last; }".into());
+    let path = ast::JPath { path: "x.A".into() };
+
+    let (project, _) =
+        project::Project::parse_all_with(&files, DuplicatePolicy::FirstWins).unwrap();
+    assert_eq!(project.type_map[&path].variables[0].name, "first");
+    assert_eq!(project.duplicates.len(), 1);
+    assert_eq!(project.duplicates[0].kept, std::path::Path::new("a/A.java"));
+    assert_eq!(
+        project.duplicates[0].dropped,
+        std::path::Path::new("b/A.java")
+    );
+
+    let (project, _) = project::Project::parse_all_with(&files, DuplicatePolicy::LastWins).unwrap();
+    assert_eq!(project.type_map[&path].variables[0].name, "last");
+    assert_eq!(project.duplicates[0].kept, std::path::Path::new("b/A.java"));
+    assert!(!project.types.contains(&ast::JPath {
+        path: "x.A.Nested".into()
+    }));
+    assert_eq!(project.type_map.len(), 1);
+
+    let err = project::Project::parse_all_with(&files, DuplicatePolicy::Error).unwrap_err();
+    assert_eq!(err.duplicates.len(), 1);
+    assert_eq!(err.duplicates[0].class.0, path);
+    assert!(err.errors.is_empty());
+
+    // without duplicates every policy parses the same project
+    files.files.remove(std::path::Path::new("b/A.java"));
+    let (project, _) = project::Project::parse_all_with(&files, DuplicatePolicy::Error).unwrap();
+    assert_eq!(project.type_map[&path].variables[0].name, "first");
+}
+
+#[test]
+fn test_package_layout() {
+    use std::path::{Path, PathBuf};
+
+    let mut files = project::Files::new();
+    files.files.insert(
+        "src/main/java/com/x/A.java".into(),
+        "package com.x; class A {}".into(),
+    );
+    files.files.insert(
+        "src/main/java/com/x/B.java".into(),
+        "package com.y; class B {}".into(),
+    );
+    files
+        .files
+        .insert("src/main/java/Main.java".into(), "class Main {}".into());
+    files
+        .files
+        .insert("other/C.java".into(), "package c; class C {}".into());
+
+    let (project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+
+    let layout = project.check_package_layout();
+    assert_eq!(layout.source_roots, [PathBuf::from("src/main/java")]);
+    let mismatches: Vec<_> = layout
+        .mismatches
+        .iter()
+        .map(|m| (m.path, m.expected.as_deref()))
+        .collect();
+    assert_eq!(
+        mismatches,
+        [
+            (Path::new("other/C.java"), None),
+            (
+                Path::new("src/main/java/com/x/B.java"),
+                Some(Path::new("src/main/java/com/y"))
+            ),
+        ]
+    );
+
+    // a root next to a deeper one doesn't swallow it
+    let mut files = project::Files::new();
+    files.files.insert(
+        "proj/tools/T.java".into(),
+        "package tools; class T {}".into(),
+    );
+    files.files.insert(
+        "proj/src/main/java/com/x/A.java".into(),
+        "package com.x; class A {}".into(),
+    );
+    files.files.insert(
+        "proj/src/main/java/com/x/B.java".into(),
+        "package com.x; class B {}".into(),
+    );
+    // while one inside the packages of another is a coincidence
+    files.files.insert(
+        "proj/src/main/java/com/x/y/C.java".into(),
+        "package y; class C {}".into(),
+    );
+
+    let (project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+
+    let layout = project.check_package_layout();
+    assert_eq!(
+        layout.source_roots,
+        [PathBuf::from("proj"), PathBuf::from("proj/src/main/java")]
+    );
+    let mismatches: Vec<_> = layout
+        .mismatches
+        .iter()
+        .map(|m| (m.path, m.expected.as_deref()))
+        .collect();
+    assert_eq!(
+        mismatches,
+        [(
+            Path::new("proj/src/main/java/com/x/y/C.java"),
+            Some(Path::new("proj/src/main/java/y"))
+        )]
+    );
+}
+
+#[test]
+fn test_visitor_order() {
+    use ast::{class::Class, functions::Function, variable::Variable, JPath};
+
+    struct Events(Vec<String>);
+    impl project::Visitor for Events {
+        type Ok = Vec<String>;
+        type Err = ();
+
+        fn visit_package_start(&mut self, package: &JPath) -> Result<(), ()> {
+            self.0.push(format!("package {}", package.path));
+            Ok(())
+        }
+        fn visit_class(&mut self, class: &Class) -> Result<(), ()> {
+            self.0.push(format!("class {}", class.class_path.path));
+            Ok(())
+        }
+        fn visit_field(&mut self, _: &Class, field: &Variable) -> Result<(), ()> {
+            self.0.push(format!("field {}", field.name));
+            Ok(())
+        }
+        fn visit_function(&mut self, _: &Class, function: &Function) -> Result<(), ()> {
+            self.0.push(format!("function {}", function.name));
+            Ok(())
+        }
+        fn visit_nested_class(&mut self, parent: &Class, nested: &Class) -> Result<(), ()> {
+            self.0.push(format!(
+                "nested {} {}",
+                parent.class_path.path, nested.class_path.path
+            ));
+            Ok(())
+        }
+        fn visit_end(&mut self) -> Result<Vec<String>, ()> {
+            Ok(std::mem::take(&mut self.0))
+        }
+    }
+
+    let mut files = project::Files::new();
+    files.files.insert(
+        "b/B.java".into(),
+        "package b; class B { void run() {} int x; static class Inner {} }".into(),
+    );
+    files
+        .files
+        .insert("a/A.java".into(), "package a; class A {}".into());
+
+    let (project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+
+    let events = project.visit(&mut Events(Vec::new())).unwrap();
+    assert_eq!(
+        events,
+        [
+            "package a",
+            "class a.A",
+            "package b",
+            "class b.B",
+            "field x",
+            "function run",
+            "nested b.B b.B.Inner",
+            "class b.B.Inner",
+        ]
+    );
+}
+
+#[test]
+fn test_neighborhood() {
+    use std::collections::HashSet;
+
+    let mut files = project::Files::new();
+    files.files.insert(
+        "x/A.java".into(),
+        "package x; class A extends B { C c; }".into(),
+    );
+    files
+        .files
+        .insert("x/B.java".into(), "package x; class B { D d; }".into());
+    files
+        .files
+        .insert("x/C.java".into(), "package x; class C {}".into());
+    files.files.insert(
+        "x/D.java".into(),
+        "package x; class D { E make() {} }".into(),
+    );
+    files
+        .files
+        .insert("x/E.java".into(), "package x; class E {}".into());
+
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    let path = |name: &str| ast::JPath {
+        path: format!("x.{name}"),
+    };
+    let names = |set: &HashSet<project::ClassPath>| {
+        let mut names: Vec<_> = set.iter().map(|class| class.0.path.clone()).collect();
+        names.sort();
+        names
+    };
+
+    let classes = project.neighborhood(&path("B"), 1);
+    assert_eq!(names(&classes), ["x.A", "x.B", "x.D"]);
+
+    let sub = project.subproject(&classes, true);
+    assert_eq!(names(&sub.stubs), ["x.C", "x.E"]);
+    assert!(sub.type_map[&path("C")].variables.is_empty());
+    assert!(sub.type_map[&path("E")].functions.is_empty());
+    assert!(sub.omitted.is_empty());
+
+    let sub = project.subproject(&classes, false);
+    assert!(sub.stubs.is_empty());
+    assert_eq!(names(&sub.omitted), ["x.C", "x.E"]);
+    assert!(!sub.type_map.contains_key(&path("C")));
+}
+
+#[test]
+fn test_literals() {
+    use tokenizer::{Token, Tokenizer};
+
+    let tokens: Vec<_> = Tokenizer::new(
+        r#"'{' '\'' "}\"{" """
+        { " "" \""" }
+        """ 0xFF_FFL 1.5e-3f .5 0x1.8p3 >>= -> :: café $x \u0041b"#,
+    )
+    .map(|(token, _)| token)
+    .collect();
+    let literals = tokens
+        .iter()
+        .filter(|token| matches!(token, Token::Literal(_)))
+        .count();
+    assert_eq!(literals, 8);
+    assert!(matches!(
+        tokens[8..],
+        [
+            Token::Operator(">>="),
+            Token::Operator("->"),
+            Token::Operator("::"),
+            Token::Ident("café"),
+            Token::Ident("$x"),
+            Token::Ident("\\u0041b"),
+        ]
+    ));
+
+    static TEST: &str = r#"
+    package x;
+    @interface Ann {
+        char open() default '{';
+        String close() default "}";
+        int value() default -1;
+    }
+    class Lex {
+        String block = """
+            }}} " "" \""" {
+            """;
+        void run() {
+            if (block == "}") { System.out.println('}'); }
+        }
+        int after() { return 1 >>> 2; }
+    }
+    "#;
+
+    let mut files = project::Files::new();
+    files.files.insert("x/Lex.java".into(), TEST.into());
+    let (project, errors) = project::Project::parse_all(&files);
+    assert!(errors.is_empty());
+
+    let ann = &project.type_map[&ast::JPath {
+        path: "x.Ann".into(),
+    }];
+    assert_eq!(ann.functions.len(), 3);
+    let lex = &project.type_map[&ast::JPath {
+        path: "x.Lex".into(),
+    }];
+    assert_eq!(lex.variables.len(), 1);
+    assert_eq!(lex.functions.len(), 2);
+}
+
+#[test]
+fn test_javadoc() {
+    use javadoc::{Javadoc, JavadocTag};
+
+    static TEST: &str = r#"
+    package x;
+
+    /**
+     * Keeps track of {@link java.util.List lists} of {@code Map<K, V>}. Only
+     * the first sentence is the summary.
+     *
+     * @param <T> the element type
+     * @since 1.2
+     */
+    /*UML_HIDE*/
+    public class Docs<T> {
+        /** The size. */
+        // not a doc comment
+        int size;
+
+        int undocumented;
+
+        /**
+         * Adds an element
+         * to the end.
+         * @param value what to add, which
+         *        can't be null
+         * @return true
+         * @throws IllegalStateException when full
+         * @deprecated use {@link #push}
+         * @author someone
+         */
+        @Deprecated
+        public boolean add(T value) { /** ignored */ return true; }
+    }
+    "#;
+
+    let result = parser::Parser::new(TEST).parse();
+    assert!(result.errors.is_empty());
+    let class = &result.classes[0];
+
+    let doc = class.meta.doc.as_ref().unwrap();
+    assert!(class.meta.hidden);
+    assert_eq!(doc.summary, "Keeps track of lists of Map<K, V>.");
+    assert!(doc
+        .description
+        .ends_with("Only\nthe first sentence is the summary."));
+    assert_eq!(
+        doc.tags,
+        [
+            JavadocTag::Param {
+                name: "<T>".into(),
+                description: "the element type".into()
+            },
+            JavadocTag::Since("1.2".into()),
+        ]
+    );
+
+    assert_eq!(
+        class.variables[0].meta.doc.as_ref().unwrap().summary,
+        "The size."
+    );
+    assert!(class.variables[1].meta.doc.is_none());
+
+    let doc = class.functions[0].meta.doc.as_ref().unwrap();
+    assert_eq!(doc.summary, "Adds an element to the end.");
+    assert!(doc.is_deprecated());
+    assert_eq!(
+        doc.tags,
+        [
+            JavadocTag::Param {
+                name: "value".into(),
+                description: "what to add, which can't be null".into()
+            },
+            JavadocTag::Return("true".into()),
+            JavadocTag::Throws {
+                exception: "IllegalStateException".into(),
+                description: "when full".into()
+            },
+            JavadocTag::Deprecated("use {@link #push}".into()),
+            JavadocTag::Other {
+                name: "author".into(),
+                text: "someone".into()
+            },
+        ]
+    );
+
+    assert_eq!(
+        Javadoc::parse("/** One line {@inheritDoc}*/").summary,
+        "One line"
+    );
+}
+
+#[test]
+fn test_annotation_arguments() {
+    use ast::{types::JType, ElementValue};
+
+    static TEST: &str = r#"
+    @javax.persistence.Table(name = "us\"ers!", indexes = {@Index(columnList = "id"), @Index("x")})
+    @RequestMapping("/api")
+    @Target({ElementType.FIELD, ElementType.METHOD,})
+    @Marker
+    @Weird(value = 1 + 2, limit = -1, types = {int[].class, java.util.List.class})
+    class A {
+        @JsonProperty("id") int id;
+        void run(@Named(value = "x") String x) {}
+    }
+    "#;
+
+    let result = parser::Parser::new(TEST).parse();
+    assert!(result.errors.is_empty());
+    let class = &result.classes[0];
+    let annotations = &class.annotations;
+    assert_eq!(annotations.annotations.len(), 5);
+
+    let table = annotations.get("Table").unwrap();
+    assert_eq!(table.name.path, "javax.persistence.Table");
+    assert_eq!(
+        table.element("name").unwrap().string().as_deref(),
+        Some("us\"ers!")
+    );
+    let Some(ElementValue::Array(indexes)) = table.element("indexes") else {
+        panic!("expected an array");
+    };
+    let Some(ElementValue::Annotation(index)) = indexes.get(1) else {
+        panic!("expected an annotation");
+    };
+    assert_eq!(index.value().unwrap().string().as_deref(), Some("x"));
+
+    let mapping = annotations.get("RequestMapping").unwrap();
+    assert_eq!(mapping.value().unwrap().string().as_deref(), Some("/api"));
+
+    let Some(ElementValue::Array(targets)) = annotations.get("Target").unwrap().value() else {
+        panic!("expected an array");
+    };
+    let targets: Vec<_> = targets
+        .iter()
+        .map(|target| match target {
+            ElementValue::EnumConstant(path) => path.path.as_str(),
+            other => panic!("expected an enum constant, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(targets, ["ElementType.FIELD", "ElementType.METHOD"]);
+
+    assert!(annotations.get("Marker").unwrap().elements.is_empty());
+
+    let weird = annotations.get("Weird").unwrap();
+    assert!(matches!(weird.value(), Some(ElementValue::Expression(e)) if e == "1 + 2"));
+    assert!(matches!(weird.element("limit"), Some(ElementValue::Literal(l)) if l == "-1"));
+    let Some(ElementValue::Array(types)) = weird.element("types") else {
+        panic!("expected an array");
+    };
+    assert!(matches!(
+        types.as_slice(),
+        [
+            ElementValue::Class(JType::PrimitiveArr(_, _)),
+            ElementValue::Class(JType::Object { path, arr: None, .. }),
+        ] if path.origional.path == "java.util.List"
+    ));
+
+    let result = parser::Parser::new("class B { @Foo(x]) int y; int z; }").parse();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.classes[0].variables[0].name, "z");
+
+    let escapes = ElementValue::Literal(r#""\101\7z\u0041\t""#.into());
+    assert_eq!(escapes.string().as_deref(), Some("A\u{7}zA\t"));
+
+    let id = class.variables[0].annotations.get("JsonProperty").unwrap();
+    assert_eq!(id.value().unwrap().string().as_deref(), Some("id"));
+    let param = &class.functions[0].parameters[0].annotations;
+    assert_eq!(
+        param
+            .get("Named")
+            .unwrap()
+            .value()
+            .unwrap()
+            .string()
+            .as_deref(),
+        Some("x")
+    );
+}
+
+#[test]
+fn test_annotation_types() {
+    use ast::{
+        class::{ElementType, RetentionPolicy},
+        functions::FunctionKind,
+        ElementValue,
+    };
+
+    static TEST: &str = r#"
+    import java.lang.annotation.*;
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target({ElementType.TYPE, ElementType.METHOD})
+    public @interface Route {
+        String value() default "";
+        String[] methods() default {"GET", "POST"};
+        int priority();
+        Class<?> handler() default Object.class;
+        int MAX = 10;
+    }
+
+    @Target(ElementType.FIELD)
+    @interface Column {}
+
+    @interface Plain {}
+    "#;
+
+    let result = parser::Parser::new(TEST).parse();
+    assert!(result.errors.is_empty());
+    let route = &result.classes[0];
+
+    assert_eq!(route.retention(), Some(RetentionPolicy::Runtime));
+    assert_eq!(
+        route.targets(),
+        Some(vec![ElementType::Type, ElementType::Method])
+    );
+    assert_eq!(route.variables[0].name, "MAX");
+
+    let defaults: Vec<_> = route
+        .functions
+        .iter()
+        .map(|function| match &function.kind {
+            FunctionKind::AnnotationElement(_, default) => default.as_ref(),
+            other => panic!("expected an annotation element, got {other:?}"),
+        })
+        .collect();
+    assert!(matches!(defaults[0], Some(ElementValue::Literal(value)) if value == "\"\""));
+    assert!(matches!(defaults[1], Some(ElementValue::Array(values)) if values.len() == 2));
+    assert!(defaults[2].is_none());
+    assert!(matches!(defaults[3], Some(ElementValue::Class(_))));
+
+    assert_eq!(result.classes[1].retention(), None);
+    assert_eq!(result.classes[1].targets(), Some(vec![ElementType::Field]));
+    assert_eq!(result.classes[2].targets(), None);
+}
+
+#[test]
+fn test_unicode_escapes() {
+    use ast::types::{JType, Primitive};
+
+    let source = source::Source::from(
+        "public \\u0063lass A\\uu0062 {\n  \\u0069nt \\u0078; // \\u000a long y;\n  String s = \"\\\\u0041\\uD83D\\uDE00\";\n}",
+    );
+    let result = parser::Parser::new(source.text()).parse();
+    assert!(result.errors.is_empty());
+    let class = &result.classes[0];
+    assert_eq!(class.name, "Ab");
+    let names: Vec<_> = class.variables.iter().map(|v| v.name).collect();
+    assert_eq!(names, ["x", "y", "s"]);
+    assert!(matches!(
+        class.variables[0].jtype,
+        JType::Primitive(Primitive::Int)
+    ));
+    // an escaped backslash doesn't start an escape
+    assert!(source.text().contains("\"\\\\u0041😀\""));
+
+    // positions point into the source as it was written
+    let mut files = project::Files::new();
+    files.files.insert(
+        "A.java".into(),
+        "class \\u0041 { \\u0046oo f; ) }".into(),
+    );
+    let (mut project, errors) = project::Project::parse_all(&files);
+    assert!(errors[0].to_string().contains("A.java:1:28"));
+    project.resolve_imports();
+    project.resolve_types();
+    let report = project.unresolved_types();
+    assert_eq!(report.by_name["Foo"][0].column, 16);
+}
+
+#[test]
+fn test_error_on_last_line() {
+    let mut files = project::Files::new();
+    files
+        .files
+        .insert("A.java".into(), "class A { int x; }\n}".into());
+
+    let (_, errors) = project::Project::parse_all(&files);
+    assert_eq!(errors.len(), 1);
+    let message = errors[0].to_string();
+    assert!(message.contains("A.java:2:1"));
+    assert!(message.contains("\n2 |"));
+
+    // columns count characters from 1, like the unresolved type report
+    files
+        .files
+        .insert("A.java".into(), "class A { String é; ) }".into());
+    let (_, errors) = project::Project::parse_all(&files);
+    assert!(errors[0].to_string().contains("A.java:1:21"));
+}
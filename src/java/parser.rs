@@ -12,8 +12,10 @@ use super::{
         generics::{GenericDefinition, GenericDefinitionPart, GenericInvoctionPart, WildcardBound},
         types::JType,
         variable::Variable,
-        Annotations, Import, Imports, JPath, Metadata, Modifiers, Visibility,
+        Annotation, Annotations, ElementValue, Import, Imports, JPath, Metadata, Modifiers,
+        Visibility,
     },
+    javadoc::Javadoc,
     tokenizer::{Peek2, Peek2able, Token, Tokenizer},
 };
 
@@ -50,6 +52,40 @@ fn unexpected_token<'a, T>(
     })
 }
 
+/// The path of tokens spelling `a.b.c`.
+fn dotted_name(tokens: &[(Token, Range<usize>)]) -> Option<JPath> {
+    let mut path = JPath::new();
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        match (index % 2, token) {
+            (0, Token::Ident(part)) => path.push_part(part),
+            (1, Token::Dot) => {}
+            _ => return None,
+        }
+    }
+    (tokens.len() % 2 == 1).then_some(path)
+}
+
+/// The type of a class literal from the tokens in front of its `.class`.
+fn class_literal(tokens: &[(Token, Range<usize>)]) -> Option<JType> {
+    let mut name = tokens;
+    let mut arr = 0u8;
+    while let [rest @ .., (Token::LBracket, _), (Token::RBracket, _)] = name {
+        name = rest;
+        arr = arr.checked_add(1)?;
+    }
+    let path = dotted_name(name)?;
+    let arr = NonZeroU8::new(arr);
+    Some(match (Primitive::from_name(&path.path), arr) {
+        (Some(primitive), None) => JType::Primitive(primitive),
+        (Some(primitive), Some(arr)) => JType::PrimitiveArr(primitive, arr),
+        (None, arr) => JType::Object {
+            path: TypePath::new(path, name[0].1.start..name[name.len() - 1].1.end),
+            generics: None,
+            arr,
+        },
+    })
+}
+
 fn expected_token<'a, T>(
     expected: &'static str,
     got: Token<'a>,
@@ -75,22 +111,34 @@ fn array_degree_too_big<'a>(range: Range<usize>) -> ParseError<'a> {
     }
 }
 
+/// Everything recovered from a single compilation unit. Members that failed
+/// to parse are left out of `classes` and reported in `errors` instead.
+#[derive(Debug)]
+pub struct ParseResult<'a> {
+    pub classes: Vec<Class<'a>>,
+    pub errors: Vec<ParseError<'a>>,
+}
+
 pub struct Parser<'a> {
+    source: &'a str,
     tokenizer: Peek2<Tokenizer<'a>>,
+    errors: Vec<ParseError<'a>>,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(data: &'a str) -> Self {
         Self {
+            source: data,
             tokenizer: Tokenizer::new(data).peek2able(),
+            errors: Vec::new(),
         }
     }
 
     pub fn expect_semi(&mut self) -> Result<(), ParseError<'a>> {
         match self.tokenizer.next() {
             Some((Token::Semicolon, _)) => Ok(()),
-            Some((got, range)) => return expected_token("Semicolon", got, range),
-            None => return expected_token_eof("Semicolon"),
+            Some((got, range)) => expected_token("Semicolon", got, range),
+            None => expected_token_eof("Semicolon"),
         }
     }
 
@@ -110,6 +158,7 @@ impl<'a> Parser<'a> {
             match (depth, self.tokenizer.next()) {
                 (_, Some((Token::LBrace, _))) => depth += 1,
                 (_, Some((Token::RBrace, _))) => depth -= 1,
+                (_, None) => return expected_token_eof("RBrace"),
                 _ => {}
             }
         }
@@ -126,12 +175,23 @@ impl<'a> Parser<'a> {
             match (depth, self.tokenizer.next()) {
                 (_, Some((Token::LPar, _))) => depth += 1,
                 (_, Some((Token::RPar, _))) => depth -= 1,
+                (_, None) => return expected_token_eof("RPar"),
                 _ => {}
             }
         }
         Ok(())
     }
 
+    /// Parses the `default` value of an annotation element, leaving the `;`
+    /// that ends it.
+    pub fn parse_default_value(&mut self) -> Result<Option<ElementValue>, ParseError<'a>> {
+        if !matches!(self.tokenizer.peek(), Some((Token::Default, _))) {
+            return Ok(None);
+        }
+        self.tokenizer.next();
+        self.parse_element_value().map(Some)
+    }
+
     pub fn remove_empty(&mut self) -> Result<(), ParseError<'a>> {
         loop {
             match self.tokenizer.peek() {
@@ -142,7 +202,24 @@ impl<'a> Parser<'a> {
         }
     }
 
-    pub fn parse(&mut self) -> Result<Class<'a>, ParseError<'a>> {
+    /// Parses a compilation unit, collecting every error instead of
+    /// stopping at the first one. All top level types declared in the file
+    /// share its imports.
+    pub fn parse(mut self) -> ParseResult<'a> {
+        let mut classes = Vec::new();
+        if let Err(error) = self.parse_compilation_unit(&mut classes) {
+            self.errors.push(error);
+        }
+        ParseResult {
+            classes,
+            errors: self.errors,
+        }
+    }
+
+    fn parse_compilation_unit(
+        &mut self,
+        classes: &mut Vec<Class<'a>>,
+    ) -> Result<(), ParseError<'a>> {
         self.remove_empty()?;
 
         let package = if let Some((Token::Package, _)) = self.tokenizer.peek() {
@@ -172,22 +249,48 @@ impl<'a> Parser<'a> {
             self.remove_empty()?;
         }
 
-        let (meta, annotations, visibility, modifiers) = self.parse_stuff()?;
-
+        let imports = std::sync::Arc::new(std::sync::Mutex::new(imports));
         let class_path_prefix = package.clone().unwrap_or_default();
 
-        let class = self.parse_class(
-            package,
-            class_path_prefix,
-            None,
-            std::sync::Arc::new(std::sync::Mutex::new(imports)),
-            meta,
-            annotations,
-            visibility,
-            modifiers,
-        )?;
+        while let Some((token, _)) = self.tokenizer.peek() {
+            // a stray brace would otherwise stop recovery from making progress
+            if let Token::RBrace = token {
+                if let Some((got, range)) = self.tokenizer.next() {
+                    self.errors.push(ParseError {
+                        kind: ParseErrorKind::UnexpectedToken {
+                            message: "Unmatched closing brace",
+                            got,
+                        },
+                        range: Some(range),
+                    });
+                }
+                continue;
+            }
 
-        Ok(class)
+            let result = match self.parse_stuff() {
+                Ok((meta, annotations, visibility, modifiers)) => self.parse_class(
+                    package.clone(),
+                    class_path_prefix.clone(),
+                    None,
+                    imports.clone(),
+                    meta,
+                    annotations,
+                    visibility,
+                    modifiers,
+                ),
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(class) => classes.push(class),
+                Err(error) => {
+                    self.recover_member(&error);
+                    self.errors.push(error);
+                }
+            }
+            self.remove_empty()?;
+        }
+
+        Ok(())
     }
 
     pub fn parse_stuff(
@@ -195,13 +298,22 @@ impl<'a> Parser<'a> {
     ) -> Result<(Metadata<'a>, Annotations, Visibility, Modifiers), ParseError<'a>> {
         let mut annotations = Annotations::new();
         let mut metas = Metadata::new();
+        if let Some((_, range)) = self.tokenizer.peek() {
+            let start = range.start;
+            metas.doc = self
+                .tokenizer
+                .get_ref()
+                .doc_comment(start)
+                .map(Javadoc::parse);
+        }
 
         loop {
             match self.tokenizer.peek() {
-                Some((Token::Annotation(annotation), _)) => {
-                    annotations.annotations.push((*annotation).into());
-                    self.tokenizer.next();
-                    self.match_parens()?;
+                // `@interface` starts an annotation type declaration
+                Some((Token::Annotation("interface"), _)) => break,
+                Some((Token::Annotation(_), _)) => {
+                    let annotation = self.parse_annotation()?;
+                    annotations.annotations.push(annotation);
                 }
                 Some((Token::UmlMeta(_), _)) => {
                     if let Some((Token::UmlMeta(meta), _)) = self.tokenizer.next() {
@@ -226,10 +338,11 @@ impl<'a> Parser<'a> {
 
         loop {
             match self.tokenizer.peek() {
-                Some((Token::Annotation(annotation), _)) => {
-                    annotations.annotations.push((*annotation).into());
-                    self.tokenizer.next();
-                    self.match_parens()?;
+                // `@interface` starts an annotation type declaration
+                Some((Token::Annotation("interface"), _)) => break,
+                Some((Token::Annotation(_), _)) => {
+                    let annotation = self.parse_annotation()?;
+                    annotations.annotations.push(annotation);
                 }
                 Some((Token::UmlMeta(_), _)) => {
                     if let Some((Token::UmlMeta(meta), _)) = self.tokenizer.next() {
@@ -245,6 +358,124 @@ impl<'a> Parser<'a> {
         Ok((metas, annotations, modifiers))
     }
 
+    /// Parses `@Name`, `@some.pkg.Name` or either followed by its arguments.
+    pub fn parse_annotation(&mut self) -> Result<Annotation, ParseError<'a>> {
+        let name = match self.tokenizer.next() {
+            Some((Token::Annotation(name), _)) => self.parse_path_with_start(name)?,
+            Some((got, range)) => return expected_token("Annotation", got, range),
+            None => return expected_token_eof("Annotation"),
+        };
+        let mut annotation = Annotation {
+            name,
+            elements: Vec::new(),
+        };
+        if !matches!(self.tokenizer.peek(), Some((Token::LPar, _))) {
+            return Ok(annotation);
+        }
+        self.tokenizer.next();
+
+        if let (Some((Token::Ident(_), _)), Some((Token::Equals, _))) = self.tokenizer.peek_both() {
+            loop {
+                let name = match self.tokenizer.next() {
+                    Some((Token::Ident(name), _)) => name,
+                    Some((got, range)) => return expected_token("Ident", got, range),
+                    None => return expected_token_eof("Ident"),
+                };
+                match self.tokenizer.next() {
+                    Some((Token::Equals, _)) => {}
+                    Some((got, range)) => return expected_token("Equals", got, range),
+                    None => return expected_token_eof("Equals"),
+                }
+                let value = self.parse_element_value()?;
+                annotation.elements.push((name.to_owned(), value));
+
+                if matches!(self.tokenizer.peek(), Some((Token::Comma, _))) {
+                    self.tokenizer.next();
+                } else {
+                    break;
+                }
+            }
+        } else if !matches!(self.tokenizer.peek(), Some((Token::RPar, _))) {
+            let value = self.parse_element_value()?;
+            annotation.elements.push(("value".to_owned(), value));
+        }
+
+        match self.tokenizer.next() {
+            Some((Token::RPar, _)) => Ok(annotation),
+            Some((got, range)) => expected_token("RPar", got, range),
+            None => expected_token_eof("RPar"),
+        }
+    }
+
+    /// Parses the value of an annotation element, which ends in front of
+    /// the `,`, `)`, `}` or `;` that follows it.
+    pub fn parse_element_value(&mut self) -> Result<ElementValue, ParseError<'a>> {
+        match self.tokenizer.peek() {
+            Some((Token::Annotation(_), _)) => {
+                return Ok(ElementValue::Annotation(Box::new(self.parse_annotation()?)))
+            }
+            Some((Token::LBrace, _)) => {
+                self.tokenizer.next();
+                let mut values = Vec::new();
+                loop {
+                    if matches!(self.tokenizer.peek(), Some((Token::RBrace, _))) {
+                        self.tokenizer.next();
+                        return Ok(ElementValue::Array(values));
+                    }
+                    values.push(self.parse_element_value()?);
+                    match self.tokenizer.next() {
+                        Some((Token::Comma, _)) => {}
+                        Some((Token::RBrace, _)) => return Ok(ElementValue::Array(values)),
+                        Some((got, range)) => return expected_token("Comma|RBrace", got, range),
+                        None => return expected_token_eof("Comma|RBrace"),
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let mut tokens = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            match self.tokenizer.peek() {
+                Some((Token::Comma | Token::RPar | Token::RBrace | Token::Semicolon, _))
+                    if depth == 0 =>
+                {
+                    break
+                }
+                Some((got @ Token::RBracket, range)) if depth == 0 => {
+                    return unexpected_token("Unmatched closing bracket", *got, range.clone())
+                }
+                Some((Token::LPar | Token::LBrace | Token::LBracket, _)) => depth += 1,
+                Some((Token::RPar | Token::RBrace | Token::RBracket, _)) => depth -= 1,
+                Some(_) => {}
+                None => return expected_token_eof("RPar"),
+            }
+            tokens.extend(self.tokenizer.next());
+        }
+
+        let (Some((_, first)), Some((_, last))) = (tokens.first(), tokens.last()) else {
+            return match self.tokenizer.next() {
+                Some((got, range)) => expected_token("ElementValue", got, range),
+                None => expected_token_eof("ElementValue"),
+            };
+        };
+        let text = &self.source[first.start..last.end];
+        Ok(match tokens.as_slice() {
+            [(Token::Literal(_), _)] | [(Token::Operator("-"), _), (Token::Literal(_), _)] => {
+                ElementValue::Literal(text.to_owned())
+            }
+            [name @ .., (Token::Dot, _), (Token::Class, _)] => match class_literal(name) {
+                Some(jtype) => ElementValue::Class(jtype),
+                None => ElementValue::Expression(text.to_owned()),
+            },
+            name => match dotted_name(name) {
+                Some(path) => ElementValue::EnumConstant(path),
+                None => ElementValue::Expression(text.to_owned()),
+            },
+        })
+    }
+
     pub fn parse_modifiers(&mut self) -> Modifiers {
         let mut modifiers = Modifiers::new();
 
@@ -322,26 +553,42 @@ impl<'a> Parser<'a> {
     pub fn parse_path(&mut self) -> Result<JPath, ParseError<'a>> {
         match self.tokenizer.next() {
             Some((Token::Ident(part), _)) => Ok(self.parse_path_with_start(part)?),
-            Some((got, range)) => return expected_token("Ident", got, range),
-            None => return expected_token_eof("Ident"),
+            Some((got, range)) => expected_token("Ident", got, range),
+            None => expected_token_eof("Ident"),
         }
     }
 
     pub fn parse_path_with_start(&mut self, start: &'a str) -> Result<JPath, ParseError<'a>> {
+        Ok(self.parse_path_with_start_range(start, 0..0)?.0)
+    }
+
+    /// Like [`Self::parse_path_with_start`] but also returns the range the
+    /// path covers in the source, `range` being the range of `start`.
+    pub fn parse_path_with_start_range(
+        &mut self,
+        start: &'a str,
+        mut range: Range<usize>,
+    ) -> Result<(JPath, Range<usize>), ParseError<'a>> {
         let mut path = JPath::new();
         path.push_part(start);
 
         while let Some((Token::Dot, _)) = self.tokenizer.peek() {
             self.tokenizer.next();
             match self.tokenizer.next() {
-                Some((Token::Ident(part), _)) => path.push_part(part),
-                Some((Token::Star, _)) => path.push_part("*"),
+                Some((Token::Ident(part), part_range)) => {
+                    path.push_part(part);
+                    range.end = part_range.end;
+                }
+                Some((Token::Star, part_range)) => {
+                    path.push_part("*");
+                    range.end = part_range.end;
+                }
                 Some((got, range)) => return expected_token("Ident|Star", got, range),
                 None => return expected_token_eof("Ident|Star"),
             }
         }
 
-        Ok(path)
+        Ok((path, range))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -475,180 +722,41 @@ impl<'a> Parser<'a> {
                 true
             }
         } {
-            while let (Some((Token::Static, _)), Some((Token::LBrace, _))) =
-                self.tokenizer.peek_both()
-            {
-                self.tokenizer.next();
-                self.remove_empty()?;
+            if self.tokenizer.peek().is_none() {
+                self.errors.push(ParseError {
+                    kind: ParseErrorKind::ExpectedTokenFoundNone { expected: "RBrace" },
+                    range: None,
+                });
+                break;
             }
 
-            let (meta, annotations, visibility, modifiers) = self.parse_stuff()?;
-
-            match self.tokenizer.peek().cloned() {
-                Some((
-                    Token::Class
-                    | Token::Interface
-                    | Token::Enum
-                    | Token::Record
-                    | Token::Annotation("interface"),
-                    _,
-                )) => {
-                    let mut class = self.parse_class(
-                        package.clone(),
-                        class_path.clone(),
-                        if modifiers.m_static() {
-                            None
-                        } else {
-                            generic_names.clone()
-                        },
-                        imports.clone(),
-                        meta,
-                        annotations,
-                        visibility,
-                        modifiers,
-                    )?;
-                    class.parent_class = Some(class_path.clone());
-                    inner_classes.push(class);
+            if let Err(error) = self.parse_member(
+                &package,
+                &class_path,
+                &generic_names,
+                &imports,
+                &mut variables,
+                &mut functions,
+                &mut inner_classes,
+            ) {
+                let closed_class = self.recover_member(&error);
+                self.errors.push(error);
+                if closed_class {
+                    break;
                 }
-                Some(start @ (Token::LAngle | Token::Ident(_), _)) => {
-                    let generics = self.parse_generic_definition()?;
-
-                    let kind = match self.tokenizer.peek_second() {
-                        Some((Token::LPar, _)) => FunctionKind::Constructor,
-                        Some((Token::LBrace, _)) => FunctionKind::CompactConstructor,
-                        _ => FunctionKind::Regular(self.parse_type()?),
-                    };
-
-                    let name = match self.tokenizer.next() {
-                        Some((Token::Ident(name), _)) => name,
-                        Some((got, range)) => return expected_token("Ident", got, range),
-                        None => return expected_token_eof("Ident"),
-                    };
+            }
 
-                    match self.tokenizer.peek().cloned() {
-                        Some((Token::LBrace, _)) => functions.push(Function {
-                            meta,
-                            annotations,
-                            visibility,
-                            modifiers,
-                            generics,
-                            kind,
-                            name,
-                            parameters: Vec::new(),
-                            throws: self.parse_function_throws()?,
-                        }),
-                        Some((Token::LPar, _)) => functions.push(Function {
-                            meta,
-                            annotations,
-                            visibility,
-                            modifiers,
-                            generics,
-                            kind,
-                            name,
-                            parameters: self.parse_function_parameters()?,
-                            throws: self.parse_function_throws()?,
-                        }),
-                        Some(_) => {
-                            if generics.is_some() {
-                                return unexpected_token(
-                                    "Cannot gave generic definition on variable",
-                                    Token::LAngle,
-                                    start.1.clone(),
-                                );
-                            }
-                            let jtype = if let FunctionKind::Regular(jtype) = kind {
-                                jtype
-                            } else {
-                                return unexpected_token(
-                                    "Expected type for variable declaration",
-                                    start.0,
-                                    start.1.clone(),
-                                );
-                            };
-
-                            let mut name = name;
-
-                            loop {
-                                let jtype = self.append_c_style_arr(jtype.clone())?;
-                                match self.tokenizer.peek() {
-                                    Some((Token::Semicolon, _)) => {
-                                        self.tokenizer.next();
-
-                                        variables.push(Variable {
-                                            meta,
-                                            annotations,
-                                            visibility,
-                                            modifiers,
-                                            jtype,
-                                            name,
-                                        });
-                                        break;
-                                    }
-                                    Some((Token::Equals, _)) => {
-                                        loop {
-                                            match self.tokenizer.next() {
-                                                Some((Token::Semicolon, _)) => break,
-                                                Some(_) => {}
-                                                None => return expected_token_eof("Semicolon"),
-                                            }
-                                        }
+            self.remove_empty()?;
+        }
 
-                                        variables.push(Variable {
-                                            meta,
-                                            annotations,
-                                            visibility,
-                                            modifiers,
-                                            jtype,
-                                            name,
-                                        });
-                                        break;
-                                    }
-                                    Some((Token::Comma, _)) => {
-                                        variables.push(Variable {
-                                            meta: meta.clone(),
-                                            annotations: annotations.clone(),
-                                            visibility,
-                                            modifiers,
-                                            jtype,
-                                            name,
-                                        });
-
-                                        self.tokenizer.next();
-                                        match self.tokenizer.next() {
-                                            Some((Token::Ident(ident), _)) => name = ident,
-                                            Some(_) => {}
-                                            None => return expected_token_eof("Ident"),
-                                        }
-                                    }
-                                    Some((got, range)) => {
-                                        return expected_token(
-                                            "Semicolon|Equals",
-                                            *got,
-                                            range.clone(),
-                                        )
-                                    }
-                                    None => return expected_token_eof("Semicolon|Equals"),
-                                }
-                            }
-                        }
-                        None => return expected_token_eof("No matching brace found at EOF"),
-                    };
-                }
-                Some((got, range)) => {
-                    return expected_token(
-                        "Class|Interface|Record|Enum|Annotation(\"interface\")|LAngle|Ident",
-                        got,
-                        range,
-                    )
-                }
-                None => {
-                    return expected_token_eof(
-                        "Class|Interface|Record|Enum|Annotation(\"interface\")|LAngle|Ident",
-                    )
+        // every method of an annotation type is one of its elements, even
+        // without a default
+        if class_type == ClassType::InterfaceA {
+            for function in &mut functions {
+                if let FunctionKind::Regular(jtype) = &function.kind {
+                    function.kind = FunctionKind::AnnotationElement(jtype.clone(), None);
                 }
             }
-
-            self.remove_empty()?;
         }
 
         Ok(Class {
@@ -673,6 +781,263 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// Parses a single member of a class body and adds it to the matching
+    /// list.
+    #[allow(clippy::too_many_arguments)]
+    fn parse_member(
+        &mut self,
+        package: &Option<JPath>,
+        class_path: &JPath,
+        generic_names: &Option<Arc<HashSet<String>>>,
+        imports: &std::sync::Arc<std::sync::Mutex<Imports>>,
+        variables: &mut Vec<Variable<'a>>,
+        functions: &mut Vec<Function<'a>>,
+        inner_classes: &mut Vec<Class<'a>>,
+    ) -> Result<(), ParseError<'a>> {
+        while let (Some((Token::Static, _)), Some((Token::LBrace, _))) = self.tokenizer.peek_both()
+        {
+            self.tokenizer.next();
+            self.remove_empty()?;
+        }
+        if let Some((Token::RBrace, _)) = self.tokenizer.peek() {
+            return Ok(());
+        }
+
+        let (meta, annotations, visibility, modifiers) = self.parse_stuff()?;
+
+        match self.tokenizer.peek().cloned() {
+            Some((
+                Token::Class
+                | Token::Interface
+                | Token::Enum
+                | Token::Record
+                | Token::Annotation("interface"),
+                _,
+            )) => {
+                let mut class = self.parse_class(
+                    package.clone(),
+                    class_path.clone(),
+                    if modifiers.m_static() {
+                        None
+                    } else {
+                        generic_names.clone()
+                    },
+                    imports.clone(),
+                    meta,
+                    annotations,
+                    visibility,
+                    modifiers,
+                )?;
+                class.parent_class = Some(class_path.clone());
+                inner_classes.push(class);
+            }
+            Some(start @ (Token::LAngle | Token::Ident(_), _)) => {
+                let generics = self.parse_generic_definition()?;
+
+                let kind = match self.tokenizer.peek_second() {
+                    Some((Token::LPar, _)) => FunctionKind::Constructor,
+                    Some((Token::LBrace, _)) => FunctionKind::CompactConstructor,
+                    _ => FunctionKind::Regular(self.parse_type()?),
+                };
+
+                let name = match self.tokenizer.next() {
+                    Some((Token::Ident(name), _)) => name,
+                    Some((got, range)) => return expected_token("Ident", got, range),
+                    None => return expected_token_eof("Ident"),
+                };
+
+                match self.tokenizer.peek().cloned() {
+                    Some((Token::LBrace, _)) => functions.push(Function {
+                        meta,
+                        annotations,
+                        visibility,
+                        modifiers,
+                        generics,
+                        kind,
+                        name,
+                        parameters: Vec::new(),
+                        throws: self.parse_function_throws()?,
+                    }),
+                    Some((Token::LPar, _)) => {
+                        let parameters = self.parse_function_parameters()?;
+                        let throws = self.parse_function_throws()?;
+                        let kind = match (kind, self.parse_default_value()?) {
+                            (FunctionKind::Regular(jtype), Some(default)) => {
+                                FunctionKind::AnnotationElement(jtype, Some(default))
+                            }
+                            (kind, _) => kind,
+                        };
+                        functions.push(Function {
+                            meta,
+                            annotations,
+                            visibility,
+                            modifiers,
+                            generics,
+                            kind,
+                            name,
+                            parameters,
+                            throws,
+                        });
+                    }
+                    Some(_) => {
+                        if generics.is_some() {
+                            return unexpected_token(
+                                "Cannot gave generic definition on variable",
+                                Token::LAngle,
+                                start.1.clone(),
+                            );
+                        }
+                        let jtype = if let FunctionKind::Regular(jtype) = kind {
+                            jtype
+                        } else {
+                            return unexpected_token(
+                                "Expected type for variable declaration",
+                                start.0,
+                                start.1.clone(),
+                            );
+                        };
+
+                        let mut name = name;
+
+                        loop {
+                            let jtype = self.append_c_style_arr(jtype.clone())?;
+                            match self.tokenizer.peek() {
+                                Some((Token::Semicolon, _)) => {
+                                    self.tokenizer.next();
+
+                                    variables.push(Variable {
+                                        meta,
+                                        annotations,
+                                        visibility,
+                                        modifiers,
+                                        jtype,
+                                        name,
+                                    });
+                                    break;
+                                }
+                                Some((Token::Equals, _)) => {
+                                    // initializers can hold lambdas and
+                                    // anonymous classes with statements of
+                                    // their own
+                                    let mut depth = 0usize;
+                                    loop {
+                                        match self.tokenizer.next() {
+                                            Some((Token::Semicolon, _)) if depth == 0 => break,
+                                            Some((Token::LBrace, _)) => depth += 1,
+                                            Some((Token::RBrace, _)) => {
+                                                depth = depth.saturating_sub(1)
+                                            }
+                                            Some(_) => {}
+                                            None => return expected_token_eof("Semicolon"),
+                                        }
+                                    }
+
+                                    variables.push(Variable {
+                                        meta,
+                                        annotations,
+                                        visibility,
+                                        modifiers,
+                                        jtype,
+                                        name,
+                                    });
+                                    break;
+                                }
+                                Some((Token::Comma, _)) => {
+                                    variables.push(Variable {
+                                        meta: meta.clone(),
+                                        annotations: annotations.clone(),
+                                        visibility,
+                                        modifiers,
+                                        jtype,
+                                        name,
+                                    });
+
+                                    self.tokenizer.next();
+                                    match self.tokenizer.next() {
+                                        Some((Token::Ident(ident), _)) => name = ident,
+                                        Some(_) => {}
+                                        None => return expected_token_eof("Ident"),
+                                    }
+                                }
+                                Some((got, range)) => {
+                                    return expected_token("Semicolon|Equals", *got, range.clone())
+                                }
+                                None => return expected_token_eof("Semicolon|Equals"),
+                            }
+                        }
+                    }
+                    None => return expected_token_eof("No matching brace found at EOF"),
+                };
+            }
+            Some((got, range)) => {
+                return expected_token(
+                    "Class|Interface|Record|Enum|Annotation(\"interface\")|LAngle|Ident",
+                    got,
+                    range,
+                )
+            }
+            None => {
+                return expected_token_eof(
+                    "Class|Interface|Record|Enum|Annotation(\"interface\")|LAngle|Ident",
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips the rest of a member that failed to parse. Stops after the `;`
+    /// or the matching `}` that ends the member, or in front of the `}`
+    /// closing the class. Returns true when the class's closing brace was
+    /// already consumed by the member that failed.
+    fn recover_member(&mut self, error: &ParseError<'a>) -> bool {
+        let got = match &error.kind {
+            ParseErrorKind::ExpectedToken { got, .. }
+            | ParseErrorKind::UnexpectedToken { got, .. } => Some(*got),
+            _ => None,
+        };
+        // the token the error was reported for may already be consumed, it
+        // then counts towards the end of the member
+        let consumed = match (self.tokenizer.peek(), &error.range) {
+            (Some((_, next)), Some(range)) => next.start != range.start,
+            (None, Some(_)) => true,
+            _ => false,
+        };
+        let mut depth = 0usize;
+        if consumed {
+            match got {
+                Some(Token::RBrace) => return true,
+                Some(Token::Semicolon) => return false,
+                Some(Token::LBrace) => depth = 1,
+                _ => {}
+            }
+        }
+
+        loop {
+            match self.tokenizer.peek() {
+                Some((Token::Semicolon, _)) if depth == 0 => {
+                    self.tokenizer.next();
+                    return false;
+                }
+                Some((Token::RBrace, _)) if depth == 0 => return false,
+                Some((Token::RBrace, _)) => {
+                    self.tokenizer.next();
+                    depth -= 1;
+                    if depth == 0 {
+                        return false;
+                    }
+                }
+                Some((Token::LBrace, _)) => {
+                    self.tokenizer.next();
+                    depth += 1;
+                }
+                Some(_) => {
+                    self.tokenizer.next();
+                }
+                None => return false,
+            }
+        }
+    }
+
     pub fn parse_type_comma_list(&mut self) -> Result<Vec<JType>, ParseError<'a>> {
         let mut list = Vec::new();
         while {
@@ -806,23 +1171,16 @@ impl<'a> Parser<'a> {
     pub fn parse_type(&mut self) -> Result<JType, ParseError<'a>> {
         enum Kind {
             Primitive(Primitive),
-            Object(JPath, Option<GenericInvoction>),
+            Object(JPath, Range<usize>, Option<GenericInvoction>),
         }
         let kind = match self.tokenizer.peek().cloned() {
-            Some((Token::Ident(ident), _)) => match ident {
-                "void" => Kind::Primitive(Primitive::Void),
-                "boolean" => Kind::Primitive(Primitive::Boolean),
-                "byte" => Kind::Primitive(Primitive::Byte),
-                "char" => Kind::Primitive(Primitive::Char),
-                "double" => Kind::Primitive(Primitive::Double),
-                "float" => Kind::Primitive(Primitive::Float),
-                "int" => Kind::Primitive(Primitive::Int),
-                "long" => Kind::Primitive(Primitive::Long),
-                "short" => Kind::Primitive(Primitive::Short),
-                _ => {
-                    let path = self.parse_path()?;
+            Some((Token::Ident(ident), range)) => match Primitive::from_name(ident) {
+                Some(primitive) => Kind::Primitive(primitive),
+                None => {
+                    self.tokenizer.next();
+                    let (path, range) = self.parse_path_with_start_range(ident, range)?;
                     let generics = self.parse_generic_invoction()?;
-                    Kind::Object(path, generics)
+                    Kind::Object(path, range, generics)
                 }
             },
             Some((got, range)) => return expected_token("Ident|PrimType", got, range),
@@ -843,8 +1201,8 @@ impl<'a> Parser<'a> {
                     JType::Primitive(primitive)
                 }
             }
-            Kind::Object(type_path, generics) => JType::Object {
-                path: TypePath::new(type_path),
+            Kind::Object(type_path, range, generics) => JType::Object {
+                path: TypePath::new(type_path, range),
                 generics,
                 arr: arr_degree,
             },
@@ -0,0 +1,202 @@
+use super::ast::{Import, Imports, JPath};
+
+/// The public top level types of `java.lang`, which every compilation unit
+/// imports implicitly.
+pub const JAVA_LANG: &[&str] = &[
+    // interfaces
+    "Appendable",
+    "AutoCloseable",
+    "CharSequence",
+    "Cloneable",
+    "Comparable",
+    "Iterable",
+    "ProcessHandle",
+    "Readable",
+    "Runnable",
+    // classes
+    "Boolean",
+    "Byte",
+    "Character",
+    "Class",
+    "ClassLoader",
+    "ClassValue",
+    "Double",
+    "Enum",
+    "Float",
+    "InheritableThreadLocal",
+    "Integer",
+    "Long",
+    "Math",
+    "Module",
+    "ModuleLayer",
+    "Number",
+    "Object",
+    "Package",
+    "Process",
+    "ProcessBuilder",
+    "Record",
+    "Runtime",
+    "RuntimePermission",
+    "SecurityManager",
+    "Short",
+    "StackTraceElement",
+    "StackWalker",
+    "StrictMath",
+    "String",
+    "StringBuffer",
+    "StringBuilder",
+    "System",
+    "Thread",
+    "ThreadGroup",
+    "ThreadLocal",
+    "Throwable",
+    "Void",
+    // exceptions
+    "ArithmeticException",
+    "ArrayIndexOutOfBoundsException",
+    "ArrayStoreException",
+    "ClassCastException",
+    "ClassNotFoundException",
+    "CloneNotSupportedException",
+    "EnumConstantNotPresentException",
+    "Exception",
+    "IllegalAccessException",
+    "IllegalArgumentException",
+    "IllegalCallerException",
+    "IllegalMonitorStateException",
+    "IllegalStateException",
+    "IllegalThreadStateException",
+    "IndexOutOfBoundsException",
+    "InstantiationException",
+    "InterruptedException",
+    "LayerInstantiationException",
+    "MatchException",
+    "NegativeArraySizeException",
+    "NoSuchFieldException",
+    "NoSuchMethodException",
+    "NullPointerException",
+    "NumberFormatException",
+    "ReflectiveOperationException",
+    "RuntimeException",
+    "SecurityException",
+    "StringIndexOutOfBoundsException",
+    "TypeNotPresentException",
+    "UnsupportedOperationException",
+    "WrongThreadException",
+    // errors
+    "AbstractMethodError",
+    "AssertionError",
+    "BootstrapMethodError",
+    "ClassCircularityError",
+    "ClassFormatError",
+    "Error",
+    "ExceptionInInitializerError",
+    "IllegalAccessError",
+    "IncompatibleClassChangeError",
+    "InstantiationError",
+    "InternalError",
+    "LinkageError",
+    "NoClassDefFoundError",
+    "NoSuchFieldError",
+    "NoSuchMethodError",
+    "OutOfMemoryError",
+    "StackOverflowError",
+    "ThreadDeath",
+    "UnknownError",
+    "UnsatisfiedLinkError",
+    "UnsupportedClassVersionError",
+    "VerifyError",
+    "VirtualMachineError",
+    // annotations
+    "Deprecated",
+    "FunctionalInterface",
+    "Override",
+    "SafeVarargs",
+    "SuppressWarnings",
+];
+
+/// Builds the implicit `import java.lang.*;` as single type imports.
+pub fn java_lang() -> Imports {
+    let mut imports = Imports::new();
+    for name in JAVA_LANG {
+        let mut path = JPath::new();
+        path.push_part("java");
+        path.push_part("lang");
+        path.push_part(name);
+        imports.add(Import::new(path, false));
+    }
+    imports
+}
+
+/// Member types of well known JDK types as `(owner, name, member)`. Types a
+/// project commonly extends also list the member types they inherit, as the
+/// supertypes of classes outside the project aren't known otherwise.
+///
+/// This is a best-effort shim rather than a model of the JDK: it lists a
+/// handful of types by hand, and a member type of any other JDK type stays
+/// unresolved.
+pub const JDK_MEMBER_TYPES: &[(&str, &str, &str)] = &[
+    (
+        "java.lang.Character",
+        "Subset",
+        "java.lang.Character.Subset",
+    ),
+    (
+        "java.lang.Character",
+        "UnicodeBlock",
+        "java.lang.Character.UnicodeBlock",
+    ),
+    (
+        "java.lang.Character",
+        "UnicodeScript",
+        "java.lang.Character.UnicodeScript",
+    ),
+    ("java.lang.Thread", "Builder", "java.lang.Thread.Builder"),
+    ("java.lang.Thread", "State", "java.lang.Thread.State"),
+    (
+        "java.lang.Thread",
+        "UncaughtExceptionHandler",
+        "java.lang.Thread.UncaughtExceptionHandler",
+    ),
+    ("java.util.AbstractMap", "Entry", "java.util.Map.Entry"),
+    (
+        "java.util.AbstractMap",
+        "SimpleEntry",
+        "java.util.AbstractMap.SimpleEntry",
+    ),
+    (
+        "java.util.AbstractMap",
+        "SimpleImmutableEntry",
+        "java.util.AbstractMap.SimpleImmutableEntry",
+    ),
+    ("java.util.EnumMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.HashMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.Hashtable", "Entry", "java.util.Map.Entry"),
+    ("java.util.IdentityHashMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.LinkedHashMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.Map", "Entry", "java.util.Map.Entry"),
+    ("java.util.NavigableMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.SortedMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.TreeMap", "Entry", "java.util.Map.Entry"),
+    ("java.util.WeakHashMap", "Entry", "java.util.Map.Entry"),
+    (
+        "java.util.concurrent.ConcurrentHashMap",
+        "Entry",
+        "java.util.Map.Entry",
+    ),
+    (
+        "java.util.concurrent.ConcurrentMap",
+        "Entry",
+        "java.util.Map.Entry",
+    ),
+];
+
+/// Looks up a member type of a JDK type in [`JDK_MEMBER_TYPES`].
+pub fn jdk_member_type(owner: &JPath, name: &str) -> Option<JPath> {
+    JDK_MEMBER_TYPES
+        .iter()
+        .find(|(jdk_owner, jdk_name, _)| owner.path == *jdk_owner && name == *jdk_name)
+        .map(|(_, _, member)| JPath {
+            path: (*member).to_owned(),
+        })
+}
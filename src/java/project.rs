@@ -1,23 +1,27 @@
 use std::{
     borrow::Borrow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
 
 use super::{
     ast::{
-        class::Class,
-        functions,
-        types::{JType, TypePath, TypeResolution},
-        Import, Imports, JPath, Metadata,
+        class::{Class, ClassType},
+        functions::{self, Function, Parameter},
+        generics::{GenericDefinition, GenericInvoctionPart, WildcardBound},
+        types::{JType, Primitive, TypePath, TypeResolution},
+        variable::Variable,
+        Import, Imports, JPath, Metadata, Visibility,
     },
     parser::{self, ParseError},
+    prelude,
+    source::Source,
 };
 
 #[derive(Default, Debug)]
 pub struct Files {
-    pub files: HashMap<PathBuf, String>,
+    pub files: HashMap<PathBuf, Source>,
 }
 
 impl Files {
@@ -25,13 +29,23 @@ impl Files {
         Self::default()
     }
 
-    pub fn load_dir(&mut self, root: impl AsRef<Path>) -> Result<(), std::io::Error> {
+    /// Loads every `.java` file under `root`, or `root` itself if it is one.
+    /// `package-info.java` and `module-info.java` don't declare classes and
+    /// are skipped.
+    pub fn load_dir(&mut self, root: impl AsRef<Path>) -> Result<(), LoadError> {
         let root = root.as_ref();
-        if std::fs::metadata(root)?.is_file() {
-            let file = std::fs::read_to_string(root)?;
-            self.files.insert(root.to_owned(), file);
+        let error = |error| LoadError {
+            path: root.to_owned(),
+            error,
+        };
+        if std::fs::metadata(root).map_err(error)?.is_file() {
+            if !is_source_file(root) {
+                return Ok(());
+            }
+            let file = std::fs::read_to_string(root).map_err(error)?;
+            self.files.insert(root.to_owned(), file.into());
         } else {
-            for path in std::fs::read_dir(root)?.flatten() {
+            for path in std::fs::read_dir(root).map_err(error)?.flatten() {
                 self.load_dir(path.path())?;
             }
         }
@@ -39,16 +53,37 @@ impl Files {
     }
 }
 
+fn is_source_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "java")
+        && !matches!(
+            path.file_name().and_then(|name| name.to_str()),
+            Some("package-info.java" | "module-info.java")
+        )
+}
+
+/// A file or directory that couldn't be read.
+#[derive(Debug)]
+pub struct LoadError {
+    pub path: PathBuf,
+    pub error: std::io::Error,
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to read {}: {}", self.path.display(), self.error)
+    }
+}
+
+impl std::error::Error for LoadError {}
+
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
 pub struct PackagePath(pub JPath);
-impl Borrow<JPath> for PackagePath{
+impl Borrow<JPath> for PackagePath {
     fn borrow(&self) -> &JPath {
         &self.0
     }
 }
 
-
-
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
 pub struct ClassPath(pub JPath);
 impl Borrow<JPath> for ClassPath {
@@ -57,15 +92,184 @@ impl Borrow<JPath> for ClassPath {
     }
 }
 
-
 #[derive(Debug, Default)]
 pub struct Project<'a> {
     pub type_map: HashMap<ClassPath, Class<'a>>,
     pub types: HashSet<ClassPath>,
     pub imports: HashMap<ClassPath, Arc<Mutex<Imports>>>,
     pub files: HashMap<ClassPath, &'a Path>,
+    pub sources: HashMap<&'a Path, &'a Source>,
     pub packages: HashMap<PackagePath, Vec<ClassPath>>,
     pub path_resolves: HashMap<JPath, Vec<ClassPath>>,
+    pub static_imports: Vec<StaticImport<'a>>,
+    pub duplicates: Vec<DuplicateClass<'a>>,
+    /// Classes of a [`Project::subproject`] that only stand in for the
+    /// targets of references leaving it, they have no members or supertypes.
+    pub stubs: HashSet<ClassPath>,
+    /// Classes left out of a [`Project::subproject`]. References to them
+    /// are dropped rather than drawn like types from outside the project.
+    pub omitted: HashSet<ClassPath>,
+}
+
+/// What [`Project::parse_all_with`] does when two files declare the same
+/// top level class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Keep the class from the file whose path sorts first.
+    #[default]
+    FirstWins,
+    /// Keep the class from the file whose path sorts last.
+    LastWins,
+    /// Fail with every duplicate, see [`DuplicateClasses`].
+    Error,
+}
+
+/// A top level class declared by more than one file.
+#[derive(Debug, Clone)]
+pub struct DuplicateClass<'a> {
+    pub class: ClassPath,
+    /// The file the class was taken from.
+    pub kept: &'a Path,
+    /// The file whose declaration was dropped.
+    pub dropped: &'a Path,
+}
+
+impl<'a> std::fmt::Display for DuplicateClass<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{BOLD}{YELLOW}warning{RESET}{BOLD}: duplicate class `{}`{RESET}",
+            self.class.0.path
+        )?;
+        writeln!(f, "  {BLUE}{BOLD}-->{RESET} {} (kept)", self.kept.display())?;
+        writeln!(
+            f,
+            "  {BLUE}{BOLD}-->{RESET} {} (dropped)",
+            self.dropped.display()
+        )
+    }
+}
+
+/// The classes declared more than once, returned by
+/// [`Project::parse_all_with`] under [`DuplicatePolicy::Error`].
+#[derive(Debug, Clone)]
+pub struct DuplicateClasses<'a> {
+    pub duplicates: Vec<DuplicateClass<'a>>,
+    /// The syntax errors found while parsing.
+    pub errors: Vec<ParseFileError<'a>>,
+}
+
+impl<'a> std::fmt::Display for DuplicateClasses<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for duplicate in &self.duplicates {
+            write!(f, "{duplicate}")?;
+        }
+        writeln!(
+            f,
+            "{BOLD}{RED}error{RESET}{BOLD}: {} class{} declared more than once{RESET}",
+            self.duplicates.len(),
+            if self.duplicates.len() == 1 {
+                " is"
+            } else {
+                "es are"
+            }
+        )
+    }
+}
+
+/// A dependency created by a static import, filled in by
+/// [`Project::resolve_imports`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticImport<'a> {
+    /// The file with the import.
+    pub file: &'a Path,
+    /// The type the members are imported from.
+    pub owner: JPath,
+    /// The imported member, `None` for `import static a.B.*;`.
+    pub member: Option<String>,
+    /// Whether `member` names a type of this project rather than a field or
+    /// method.
+    pub is_type: bool,
+}
+
+/// Selects which members are kept by [`Project::retain_members`].
+#[derive(Debug, Clone, Copy)]
+pub struct MemberFilter {
+    pub min_visibility: Visibility,
+    pub fields: bool,
+    /// Methods other than constructors.
+    pub methods: bool,
+    pub constructors: bool,
+    /// Getters and setters, see [`is_accessor`].
+    pub accessors: bool,
+    pub statics: bool,
+}
+
+impl Default for MemberFilter {
+    fn default() -> Self {
+        Self {
+            min_visibility: Visibility::Private,
+            fields: true,
+            methods: true,
+            constructors: true,
+            accessors: true,
+            statics: true,
+        }
+    }
+}
+
+impl MemberFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `variable`, a field of `class`, passes the filter.
+    pub fn keeps_variable(&self, class: &Class, variable: &Variable) -> bool {
+        self.fields
+            && class
+                .member_visibility(variable.visibility)
+                .is_at_least(self.min_visibility)
+            && (self.statics || !variable.modifiers.m_static())
+    }
+
+    /// Whether `function`, a method of `class`, passes the filter.
+    pub fn keeps_function(&self, class: &Class, function: &Function) -> bool {
+        let kept = match function.kind {
+            functions::FunctionKind::Regular(_) => {
+                self.methods && (self.accessors || !is_accessor(function))
+            }
+            functions::FunctionKind::Constructor | functions::FunctionKind::CompactConstructor => {
+                self.constructors
+            }
+            functions::FunctionKind::AnnotationElement(..) => self.methods,
+        };
+        kept && class
+            .member_visibility(function.visibility)
+            .is_at_least(self.min_visibility)
+            && (self.statics || !function.modifiers.m_static())
+    }
+}
+
+/// Whether a method looks like a getter, `getX()` or `isX()` returning a
+/// value, or a setter, `setX(value)`.
+pub fn is_accessor(function: &Function) -> bool {
+    fn has_prefix(name: &str, prefix: &str) -> bool {
+        name.strip_prefix(prefix)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(char::is_uppercase)
+    }
+    let functions::FunctionKind::Regular(ret) = &function.kind else {
+        return false;
+    };
+    let name = function.name;
+    match function.parameters.len() {
+        0 => {
+            !matches!(ret, JType::Primitive(Primitive::Void))
+                && (has_prefix(name, "get") || has_prefix(name, "is"))
+        }
+        1 => has_prefix(name, "set"),
+        _ => false,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -77,7 +281,7 @@ pub struct ParseFileError<'a> {
 
 const BOLD: &str = "\x1b[1m";
 const RED: &str = "\x1b[31m";
-// const YELLOW: &str = "\x1b[33m";
+const YELLOW: &str = "\x1b[33m";
 const BLUE: &str = "\x1b[34m";
 // const GREEN: &str = "\x1b[32m";
 const RESET: &str = "\x1b[0;22m";
@@ -105,15 +309,16 @@ impl<'a> std::fmt::Display for ParseFileError<'a> {
                 .unwrap_or(0);
             let line_end = self.contents[range.end..]
                 .find('\n')
-                .map(|v| v + range.end - 1)
+                .map(|v| v + range.end)
                 .unwrap_or(self.contents.len());
-            let col = range.start - line_start;
+            // 1 based and counted in characters like the unresolved type report
+            let col = line_column(self.contents, range.start).1;
             let lines = self.contents[..range.start]
                 .chars()
                 .filter(|v| *v == '\n')
                 .count()
                 + 1;
-            let msg = &self.contents[line_start..=line_end];
+            let msg = &self.contents[line_start..line_end];
             (
                 lines,
                 col,
@@ -123,7 +328,7 @@ impl<'a> std::fmt::Display for ParseFileError<'a> {
         } else {
             let lines = self.contents.chars().filter(|v| *v == '\n').count() + 1;
             let line_start = self.contents.rfind('\n').map(|v| v + 1).unwrap_or(0);
-            let col = self.contents.len() - line_start;
+            let col = line_column(self.contents, self.contents.len()).1;
             let msg = &self.contents[line_start..];
             (lines, col, msg, 0..0)
         };
@@ -177,35 +382,101 @@ impl<'a> std::fmt::Display for ParseFileError<'a> {
 }
 
 impl<'a> Project<'a> {
-    pub fn parse_all(files: &'a Files) -> Result<Project<'a>, Vec<ParseFileError<'a>>> {
+    /// Parses every `.java` file. Files with syntax errors still contribute
+    /// whatever could be recovered from them, the errors are returned
+    /// alongside the project.
+    pub fn parse_all(files: &'a Files) -> (Project<'a>, Vec<ParseFileError<'a>>) {
+        Self::parse_files(files, DuplicatePolicy::default())
+    }
+
+    /// Like [`Project::parse_all`], resolving classes declared by more than
+    /// one file with `policy`. Files are visited in path order so the same
+    /// declaration wins on every run, every duplicate is recorded in
+    /// [`Project::duplicates`]. Under [`DuplicatePolicy::Error`] any
+    /// duplicate is an error instead.
+    pub fn parse_all_with(
+        files: &'a Files,
+        policy: DuplicatePolicy,
+    ) -> Result<(Project<'a>, Vec<ParseFileError<'a>>), DuplicateClasses<'a>> {
+        let (project, errors) = Self::parse_files(files, policy);
+        if policy == DuplicatePolicy::Error && !project.duplicates.is_empty() {
+            return Err(DuplicateClasses {
+                duplicates: project.duplicates,
+                errors,
+            });
+        }
+        Ok((project, errors))
+    }
+
+    fn parse_files(
+        files: &'a Files,
+        policy: DuplicatePolicy,
+    ) -> (Project<'a>, Vec<ParseFileError<'a>>) {
         let mut myself = Self::default();
         let mut vec = Vec::new();
-        for (path, contents) in &files.files {
+        let mut sorted: Vec<_> = files.files.iter().collect();
+        sorted.sort_by_key(|(path, _)| *path);
+        for (path, contents) in sorted {
             if !matches!(
                 path.as_path().extension().map(|v| v.to_str()),
                 Some(Some("java"))
             ) {
                 continue;
             }
-            let result = parser::Parser::new(contents).parse();
-            match result {
-                Ok(class) => {
-                    myself
-                        .imports
-                        .insert(ClassPath(class.class_path.clone()), class.imports.clone());
-                    myself.add_class(path, class)
-                }
-                Err(error) => vec.push(ParseFileError {
+            myself.sources.insert(path, contents);
+            let result = parser::Parser::new(contents.text()).parse();
+            vec.extend(result.errors.into_iter().map(|mut error| {
+                error.range = error.range.map(|range| contents.original_range(range));
+                ParseFileError {
                     path,
-                    contents,
+                    contents: contents.original(),
                     error,
-                }),
+                }
+            }));
+            for class in result.classes {
+                let class_path = ClassPath(class.class_path.clone());
+                if let Some(&existing) = myself.files.get(&class_path) {
+                    let (kept, dropped) = match policy {
+                        DuplicatePolicy::FirstWins | DuplicatePolicy::Error => {
+                            (existing, path.as_path())
+                        }
+                        DuplicatePolicy::LastWins => (path.as_path(), existing),
+                    };
+                    myself.duplicates.push(DuplicateClass {
+                        class: class_path.clone(),
+                        kept,
+                        dropped,
+                    });
+                    if kept == existing {
+                        continue;
+                    }
+                    myself.remove_class(&class_path);
+                }
+                myself.imports.insert(class_path, class.imports.clone());
+                myself.add_class(path, class)
             }
         }
-        if vec.is_empty() {
-            Ok(myself)
-        } else {
-            Err(vec)
+        (myself, vec)
+    }
+
+    /// Removes a class and its nested classes.
+    fn remove_class(&mut self, path: &ClassPath) {
+        for nested in self.path_resolves.remove(&path.0).unwrap_or_default() {
+            self.remove_class(&nested);
+        }
+        self.type_map.remove(path);
+        self.types.remove(path);
+        self.files.remove(path);
+        self.imports.remove(path);
+
+        for classes in self.packages.values_mut() {
+            classes.retain(|class| class != path);
+        }
+        self.packages.retain(|_, classes| !classes.is_empty());
+        let mut parent = path.0.clone();
+        parent.pop_part();
+        if let Some(siblings) = self.path_resolves.get_mut(&parent) {
+            siblings.retain(|class| class != path);
         }
     }
 
@@ -234,41 +505,99 @@ impl<'a> Project<'a> {
             .insert(ClassPath(class.class_path.clone()), class);
     }
 
+    /// `class` followed by the classes enclosing it, from the inside out.
+    pub fn enclosing_classes<'b>(
+        &'b self,
+        class: &'b Class<'b>,
+    ) -> impl Iterator<Item = &'b Class<'b>> + 'b {
+        std::iter::successors(Some(class), |class| {
+            class
+                .parent_class
+                .as_ref()
+                .and_then(|parent| self.type_map.get(parent))
+        })
+    }
+
+    /// Whether `class`, or any class enclosing it, is marked with
+    /// `UML_HIDE`.
+    pub fn is_hidden(&self, class: &Class) -> bool {
+        self.enclosing_classes(class).any(|class| class.meta.hidden)
+    }
+
+    /// Drops every field and method of every class that doesn't pass the
+    /// filter.
+    pub fn retain_members(&mut self, filter: &MemberFilter) {
+        for class in self.type_map.values_mut() {
+            // the filter looks at the owning class, so the members are
+            // taken out while it runs
+            let mut variables = std::mem::take(&mut class.variables);
+            variables.retain(|variable| filter.keeps_variable(class, variable));
+            class.variables = variables;
+            let mut functions = std::mem::take(&mut class.functions);
+            functions.retain(|function| filter.keeps_function(class, function));
+            class.functions = functions;
+        }
+    }
+
     pub fn resolve_imports(&mut self) {
+        self.static_imports.clear();
+        // the top level classes of a file share its imports
+        let mut files = HashSet::new();
         for (path, import) in &mut self.imports {
+            let file = self.files.get(path).copied();
             let mut lock = import.lock().unwrap();
-            let Imports { name_map, wildcard } = &mut *lock;
+            let Imports {
+                wildcard,
+                package_map,
+                wildcard_map,
+                static_name_map,
+                static_wildcard,
+                ..
+            } = &mut *lock;
 
-            let mut path = path.clone();
-            for import in self.path_resolves.get(&path.0).unwrap_or(&Vec::new()) {
-                if !name_map.contains_key(import.0.last()) {
-                    name_map.insert(
-                        import.0.last().to_owned(),
-                        Import {
-                            path: import.0.clone(),
-                            is_static: false,
-                        },
-                    );
+            if let Some(file) = file.filter(|file| files.insert(*file)) {
+                for import in static_name_map.values() {
+                    let mut owner = import.path.clone();
+                    owner.pop_part();
+                    self.static_imports.push(StaticImport {
+                        file,
+                        owner,
+                        member: Some(import.path.last().to_owned()),
+                        is_type: self.types.contains(&import.path),
+                    });
+                }
+                for import in static_wildcard.iter() {
+                    let mut owner = import.path.clone();
+                    owner.pop_part();
+                    self.static_imports.push(StaticImport {
+                        file,
+                        owner,
+                        member: None,
+                        is_type: false,
+                    });
                 }
             }
+
+            // member types are found through the enclosing classes while
+            // resolving, only the other types of the package go in here
+            let mut path = path.clone();
             path.0.pop_part();
             for import in self.path_resolves.get(&path.0).unwrap_or(&Vec::new()) {
-                if !name_map.contains_key(import.0.last()) {
-                    name_map.insert(
-                        import.0.last().to_owned(),
-                        Import {
-                            path: import.0.clone(),
-                            is_static: false,
-                        },
-                    );
-                }
+                package_map.insert(
+                    import.0.last().to_owned(),
+                    Import {
+                        path: import.0.clone(),
+                        is_static: false,
+                    },
+                );
             }
 
-            for mut import in wildcard.drain(..) {
-                import.path.pop_part();
-                for import in self.path_resolves.get(&import.path).unwrap_or(&Vec::new()) {
-                    if !name_map.contains_key(import.0.last()) {
-                        name_map.insert(
+            for import in wildcard.iter() {
+                let mut package = import.path.clone();
+                package.pop_part();
+                for import in self.path_resolves.get(&package).unwrap_or(&Vec::new()) {
+                    if !wildcard_map.contains_key(import.0.last()) {
+                        wildcard_map.insert(
                             import.0.last().to_owned(),
                             Import {
                                 path: import.0.clone(),
@@ -279,21 +608,67 @@ impl<'a> Project<'a> {
                 }
             }
         }
+        self.static_imports.sort_by(|a, b| {
+            (a.file, &a.owner.path, &a.member).cmp(&(b.file, &b.owner.path, &b.member))
+        });
+        self.static_imports.dedup();
     }
 
     pub fn resolve_types(&mut self) {
         let Self {
-            type_map, types, ..
+            type_map,
+            types,
+            packages,
+            ..
         } = self;
 
-        let prelude = Imports::new();
+        // project classes reach the resolver through the class imports,
+        // which are searched first and so shadow java.lang
+        let prelude = prelude::java_lang();
+
+        // inherited member types can only be found once the supertypes are
+        // known, and a supertype can itself be an inherited member type, so
+        // resolve the supertypes until nothing changes
+        let mut supertypes = HashMap::new();
+        for _ in 0..=type_map.len() {
+            let mut next = HashMap::new();
+            for class in type_map.values_mut() {
+                let class_imports = class.imports.clone();
+                let class_imports = class_imports.lock().unwrap();
+                // the members of a class aren't in scope in its own header
+                let mut scope = class.class_path.clone();
+                scope.pop_part();
+                let resolver = TypeResolve {
+                    prelude: &prelude,
+                    class_imports: &class_imports,
+                    classes: types,
+                    packages,
+                    supertypes: &supertypes,
+                    scope: types.contains(&scope).then_some(&scope),
+                    generics: class.generic_names.clone().unwrap_or_default(),
+                };
+                next.insert(
+                    ClassPath(class.class_path.clone()),
+                    resolver.resolve_header(class),
+                );
+            }
+            if next == supertypes {
+                break;
+            }
+            supertypes = next;
+        }
+
         for class in type_map.values_mut() {
             let class_imports = class.imports.clone();
             let class_imports = class_imports.lock().unwrap();
+            let scope = class.class_path.clone();
             let resolver = TypeResolve {
                 prelude: &prelude,
                 class_imports: &class_imports,
                 classes: types,
+                packages,
+                supertypes: &supertypes,
+                scope: Some(&scope),
                 generics: class.generic_names.clone().unwrap_or_default(),
             };
             resolver.resolve_class(class);
@@ -305,23 +680,181 @@ struct TypeResolve<'a> {
     prelude: &'a Imports,
     class_imports: &'a Imports,
     classes: &'a HashSet<ClassPath>,
+    packages: &'a HashMap<PackagePath, Vec<ClassPath>>,
+    /// The resolved direct supertypes of every class of the project.
+    supertypes: &'a HashMap<ClassPath, Vec<JPath>>,
+    /// The innermost class whose member types are in scope.
+    scope: Option<&'a JPath>,
     generics: Arc<HashSet<String>>,
 }
 
 impl<'a> TypeResolve<'a> {
     pub fn resolve(&self, jtype: &mut TypePath) {
-        if self.classes.contains(&jtype.origional) {
-            jtype.resolved = TypeResolution::Some(jtype.origional.clone());
+        // type parameters shadow every type with the same simple name
+        if self.generics.contains(&jtype.origional.path) {
+            jtype.resolved = TypeResolution::Generic;
+        } else if let Some(resolved) = self.resolve_path(&jtype.origional) {
+            jtype.resolved = TypeResolution::Some(resolved);
+        }
+    }
+
+    /// Resolves the first segment of `path` as a simple type name, or the
+    /// longest prefix naming a project class as a fully qualified name, and
+    /// every remaining segment as a member type of the one before it. A
+    /// qualified name whose first segment isn't a type in scope names a
+    /// package (JLS 6.5.2), a package outside of the project can't be
+    /// checked so the name resolves to itself.
+    fn resolve_path(&self, path: &JPath) -> Option<JPath> {
+        let segments: Vec<&str> = path.path.split('.').collect();
+        let (mut resolved, rest) = match self.resolve_simple(segments[0]) {
+            Some(resolved) => (resolved, &segments[1..]),
+            None if segments.len() == 1 => return None,
+            None => (1..segments.len())
+                .find_map(|len| {
+                    let prefix = JPath {
+                        path: segments[..len].join("."),
+                    };
+                    self.classes
+                        .contains(&prefix)
+                        .then_some((prefix, &segments[len..]))
+                })
+                .or_else(|| {
+                    let in_project = (1..segments.len()).any(|len| {
+                        self.packages.contains_key(&JPath {
+                            path: segments[..len].join("."),
+                        })
+                    });
+                    (!in_project).then_some((path.clone(), &[][..]))
+                })?,
+        };
+        for segment in rest {
+            resolved = match self.find_member(&resolved, segment, &mut HashSet::new()) {
+                Some(member) => member,
+                // a type outside of the project, assume it's declared there
+                None => {
+                    resolved.push_part(segment);
+                    resolved
+                }
+            };
+        }
+        Some(resolved)
+    }
+
+    /// Looks up a simple type name the way JLS 6.5.5.1 does: member types of
+    /// the enclosing classes from the inside out, then the single imports,
+    /// the types of the package and last the on-demand imports, each
+    /// shadowing the ones after it (JLS 6.4.1).
+    fn resolve_simple(&self, name: &str) -> Option<JPath> {
+        let mut scope = self.scope.cloned();
+        while let Some(class) = scope {
+            if let Some(member) = self.find_member(&class, name, &mut HashSet::new()) {
+                return Some(member);
+            }
+            let mut outer = class;
+            outer.pop_part();
+            scope = self.classes.contains(&outer).then_some(outer);
+        }
+
+        let imports = self.class_imports;
+        if let Some(resolved) = imports.name_map.get(name) {
+            Some(resolved.path.clone())
+        } else if let Some(resolved) = self.resolve_static_single(name) {
+            Some(resolved)
+        } else if let Some(resolved) = imports.package_map.get(name) {
+            Some(resolved.path.clone())
+        } else if let Some(resolved) = imports.wildcard_map.get(name) {
+            Some(resolved.path.clone())
+        } else if let Some(resolved) = self.resolve_static_wildcard(name) {
+            Some(resolved)
         } else {
-            let start = jtype.origional.first();
-            if let Some(resolved) = self.class_imports.name_map.get(start) {
-                jtype.resolved = TypeResolution::Some(resolved.path.clone());
-            } else if let Some(resolved) = self.prelude.name_map.get(start) {
-                jtype.resolved = TypeResolution::Some(resolved.path.clone());
-            } else if self.generics.contains(&jtype.origional.path) {
-                jtype.resolved = TypeResolution::Generic;
+            self.prelude
+                .name_map
+                .get(name)
+                .map(|resolved| resolved.path.clone())
+        }
+    }
+
+    /// Finds a member type called `name` declared in `owner` or inherited by
+    /// it through its supertypes.
+    fn find_member(&self, owner: &JPath, name: &str, seen: &mut HashSet<JPath>) -> Option<JPath> {
+        // cyclic inheritance is an error in java but can still be parsed
+        if !seen.insert(owner.clone()) {
+            return None;
+        }
+        let mut member = owner.clone();
+        member.push_part(name);
+        if self.classes.contains(&member) {
+            return Some(member);
+        }
+        if let Some(member) = prelude::jdk_member_type(owner, name) {
+            return Some(member);
+        }
+        self.supertypes
+            .get(owner)
+            .into_iter()
+            .flatten()
+            .find_map(|supertype| self.find_member(supertype, name, seen))
+    }
+
+    /// Resolves the `extends`, `implements` and `permits` clauses of a class
+    /// and returns the supertypes that could be resolved.
+    fn resolve_header(&self, class: &mut Class) -> Vec<JPath> {
+        for permits in class.permits.iter_mut().flatten() {
+            self.resolve_type(permits);
+        }
+
+        if let Some(generics) = &mut class.generics {
+            for definition in &mut generics.definitions {
+                for bound in definition.extend_bound.iter_mut().flatten() {
+                    self.resolve_type(bound);
+                }
+            }
+        }
+
+        let mut resolved = Vec::new();
+        let extends = class.extends.iter_mut().flatten();
+        for supertype in extends.chain(class.implements.iter_mut().flatten()) {
+            self.resolve_type(supertype);
+            if let JType::Object { path, .. } = supertype {
+                if let TypeResolution::Some(path) = &path.resolved {
+                    resolved.push(path.clone());
+                }
             }
         }
+        resolved
+    }
+
+    /// Returns a resolver that also sees the type parameters of a generic
+    /// method or constructor.
+    fn with_generics(&self, generics: &GenericDefinition) -> TypeResolve<'a> {
+        let mut names = (*self.generics).clone();
+        names.extend(generics.definitions.iter().map(|def| def.name.clone()));
+        TypeResolve {
+            generics: Arc::new(names),
+            ..*self
+        }
+    }
+
+    /// Looks `name` up as a member type imported with `import static a.B.name;`,
+    /// which can also import a field or method of the same name.
+    fn resolve_static_single(&self, name: &str) -> Option<JPath> {
+        let import = self.class_imports.static_name_map.get(name)?;
+        let mut owner = import.path.clone();
+        owner.pop_part();
+        self.find_member(&owner, name, &mut HashSet::new())
+    }
+
+    /// Looks `name` up as a member type of the types imported with
+    /// `import static a.B.*;`.
+    fn resolve_static_wildcard(&self, name: &str) -> Option<JPath> {
+        self.class_imports
+            .static_wildcard
+            .iter()
+            .find_map(|import| {
+                let mut owner = import.path.clone();
+                owner.pop_part();
+                self.find_member(&owner, name, &mut HashSet::new())
+            })
     }
 
     fn resolve_type(&self, jtype: &mut JType) {
@@ -355,79 +888,740 @@ impl<'a> TypeResolve<'a> {
     }
 
     fn resolve_class(&self, class: &mut Class) {
+        // the header was already resolved by `resolve_header`
         self.resolve_meta(&mut class.meta);
-        for extends in class.extends.as_mut().unwrap_or(&mut Vec::new()) {
-            self.resolve_type(extends);
-        }
-        for implements in class.implements.as_mut().unwrap_or(&mut Vec::new()) {
-            self.resolve_type(implements);
-        }
-        for permits in class.permits.as_mut().unwrap_or(&mut Vec::new()) {
-            self.resolve_type(permits);
-        }
 
         for variables in &mut class.variables {
             self.resolve_meta(&mut variables.meta);
             self.resolve_type(&mut variables.jtype);
         }
 
-        for functions in &mut class.functions {
-            self.resolve_meta(&mut functions.meta);
-            if let Some(generics) = &mut functions.generics {
-                for definition in &mut generics.definitions {
-                    if let Some(bounds) = &mut definition.extend_bound {
-                        for bound in bounds {
-                            self.resolve_type(bound);
-                        }
+        for function in &mut class.functions {
+            match &function.generics {
+                Some(generics) => self.with_generics(generics).resolve_function(function),
+                None => self.resolve_function(function),
+            }
+        }
+    }
+
+    fn resolve_function(&self, function: &mut functions::Function) {
+        self.resolve_meta(&mut function.meta);
+        if let Some(generics) = &mut function.generics {
+            for definition in &mut generics.definitions {
+                if let Some(bounds) = &mut definition.extend_bound {
+                    for bound in bounds {
+                        self.resolve_type(bound);
                     }
                 }
             }
-            for param in &mut functions.parameters {
-                self.resolve_type(&mut param.jtype);
+        }
+        for param in &mut function.parameters {
+            self.resolve_type(&mut param.jtype);
+        }
+
+        if let Some(throws) = &mut function.throws {
+            for throw in throws {
+                self.resolve_type(throw);
             }
+        }
+
+        if let functions::FunctionKind::Regular(jtype)
+        | functions::FunctionKind::AnnotationElement(jtype, _) = &mut function.kind
+        {
+            self.resolve_type(jtype);
+        }
+    }
+
+    fn resolve_meta(&self, _meta: &mut Metadata) {}
+}
+
+// ----------------------- Unresolved types
+
+/// What an unresolved type was used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeUsage {
+    Extends,
+    Implements,
+    Permits,
+    TypeBound,
+    Field,
+    Parameter,
+    ReturnType,
+    Throws,
+}
+
+impl std::fmt::Display for TypeUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TypeUsage::Extends => "supertype",
+            TypeUsage::Implements => "implemented interface",
+            TypeUsage::Permits => "permitted subclass",
+            TypeUsage::TypeBound => "type parameter bound",
+            TypeUsage::Field => "field type",
+            TypeUsage::Parameter => "parameter type",
+            TypeUsage::ReturnType => "return type",
+            TypeUsage::Throws => "thrown type",
+        })
+    }
+}
+
+/// A type reference that [`Project::resolve_types`] couldn't resolve.
+#[derive(Debug, Clone)]
+pub struct UnresolvedType<'a> {
+    /// The type as it was written.
+    pub name: JPath,
+    pub usage: TypeUsage,
+    /// The class the reference is in.
+    pub class: ClassPath,
+    /// The field or method the reference is in.
+    pub member: Option<&'a str>,
+    pub path: &'a Path,
+    /// 1 based line and column of the reference.
+    pub line: usize,
+    pub column: usize,
+    /// The packages and classes outside of the project imported on demand
+    /// by the file, any of which could declare the type.
+    pub on_demand: Vec<JPath>,
+}
+
+impl<'a> std::fmt::Display for UnresolvedType<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{BLUE}{BOLD}-->{RESET} {}:{}:{} `{}` as {} of {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.name.path,
+            self.usage,
+            self.class.0
+        )?;
+        if let Some(member) = self.member {
+            write!(f, ".{member}")?;
+        }
+        Ok(())
+    }
+}
 
-            if let Some(throws) = &mut functions.throws {
-                for throw in throws {
-                    self.resolve_type(throw);
+/// Every unresolved type reference of a project, see
+/// [`Project::unresolved_types`].
+#[derive(Debug, Clone, Default)]
+pub struct UnresolvedTypes<'a> {
+    /// The references grouped by the simple name of the type, each group
+    /// ordered by file and position.
+    pub by_name: BTreeMap<String, Vec<UnresolvedType<'a>>>,
+    /// The references to simple names that could come from an on-demand
+    /// import of a package or class outside of the project, like `List`
+    /// with `import java.util.*;`, grouped like [`Self::by_name`].
+    pub on_demand: BTreeMap<String, Vec<UnresolvedType<'a>>>,
+}
+
+impl<'a> UnresolvedTypes<'a> {
+    /// Whether every reference was resolved, references that could come
+    /// from on-demand imports included.
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty() && self.on_demand.is_empty()
+    }
+}
+
+impl<'a> std::fmt::Display for UnresolvedTypes<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, references) in &self.by_name {
+            writeln!(
+                f,
+                "{BOLD}{YELLOW}warning{RESET}{BOLD}: unresolved type `{name}` ({} reference{}){RESET}",
+                references.len(),
+                if references.len() == 1 { "" } else { "s" }
+            )?;
+            for reference in references {
+                writeln!(f, "  {reference}")?;
+            }
+        }
+        for (name, references) in &self.on_demand {
+            let mut imports: Vec<String> = references
+                .iter()
+                .flat_map(|reference| &reference.on_demand)
+                .map(|import| format!("`{}.*`", import.path))
+                .collect();
+            imports.sort();
+            imports.dedup();
+            writeln!(
+                f,
+                "{BOLD}{BLUE}note{RESET}{BOLD}: `{name}` ({} reference{}) may come from {}{RESET}",
+                references.len(),
+                if references.len() == 1 { "" } else { "s" },
+                imports.join(", ")
+            )?;
+            for reference in references {
+                writeln!(f, "  {reference}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Project<'a> {
+    /// Lists every type reference left unresolved by
+    /// [`Project::resolve_types`]. Simple names that an on-demand import
+    /// of something outside of the project could declare are listed apart,
+    /// the project can't tell whether they are missing.
+    pub fn unresolved_types(&self) -> UnresolvedTypes<'_> {
+        let mut report: BTreeMap<String, Vec<UnresolvedType>> = BTreeMap::new();
+        let mut on_demand_report: BTreeMap<String, Vec<UnresolvedType>> = BTreeMap::new();
+        for (class_path, class) in &self.type_map {
+            let Some(&path) = self.files.get(class_path) else {
+                continue;
+            };
+            let Some(&source) = self.sources.get(path) else {
+                continue;
+            };
+
+            let on_demand: Vec<JPath> = {
+                let imports = class.imports.lock().unwrap();
+                imports
+                    .wildcard
+                    .iter()
+                    .chain(&imports.static_wildcard)
+                    .map(|import| {
+                        let mut owner = import.path.clone();
+                        owner.pop_part();
+                        owner
+                    })
+                    .filter(|owner| {
+                        !self.path_resolves.contains_key(owner) && !self.types.contains(owner)
+                    })
+                    .collect()
+            };
+
+            let mut found = Vec::new();
+            for jtype in class.extends.iter().flatten() {
+                unresolved_paths(jtype, TypeUsage::Extends, None, &mut found);
+            }
+            for jtype in class.implements.iter().flatten() {
+                unresolved_paths(jtype, TypeUsage::Implements, None, &mut found);
+            }
+            for jtype in class.permits.iter().flatten() {
+                unresolved_paths(jtype, TypeUsage::Permits, None, &mut found);
+            }
+            for definition in class.generics.iter().flat_map(|gen| &gen.definitions) {
+                for jtype in definition.extend_bound.iter().flatten() {
+                    unresolved_paths(jtype, TypeUsage::TypeBound, None, &mut found);
+                }
+            }
+            for variable in &class.variables {
+                unresolved_paths(
+                    &variable.jtype,
+                    TypeUsage::Field,
+                    Some(variable.name),
+                    &mut found,
+                );
+            }
+            for function in &class.functions {
+                let member = Some(function.name);
+                for definition in function.generics.iter().flat_map(|gen| &gen.definitions) {
+                    for jtype in definition.extend_bound.iter().flatten() {
+                        unresolved_paths(jtype, TypeUsage::TypeBound, member, &mut found);
+                    }
+                }
+                for param in &function.parameters {
+                    unresolved_paths(&param.jtype, TypeUsage::Parameter, member, &mut found);
+                }
+                if let functions::FunctionKind::Regular(jtype)
+                | functions::FunctionKind::AnnotationElement(jtype, _) = &function.kind
+                {
+                    unresolved_paths(jtype, TypeUsage::ReturnType, member, &mut found);
+                }
+                for jtype in function.throws.iter().flatten() {
+                    unresolved_paths(jtype, TypeUsage::Throws, member, &mut found);
                 }
             }
 
-            if let functions::FunctionKind::Regular(jtype) = &mut functions.kind {
-                self.resolve_type(jtype);
+            for (type_path, usage, member) in found {
+                let (line, column) = line_column(
+                    source.original(),
+                    source.original_offset(type_path.range.start),
+                );
+                // only a simple name can refer to an imported type
+                let is_simple = !type_path.origional.path.contains('.');
+                let on_demand = if is_simple {
+                    on_demand.clone()
+                } else {
+                    Vec::new()
+                };
+                let report = if on_demand.is_empty() {
+                    &mut report
+                } else {
+                    &mut on_demand_report
+                };
+                report
+                    .entry(type_path.origional.last().to_owned())
+                    .or_default()
+                    .push(UnresolvedType {
+                        name: type_path.origional.clone(),
+                        usage,
+                        class: ClassPath(class.class_path.clone()),
+                        member,
+                        path,
+                        line,
+                        column,
+                        on_demand,
+                    });
             }
         }
+        for references in report.values_mut().chain(on_demand_report.values_mut()) {
+            references.sort_by(|a, b| (a.path, a.line, a.column).cmp(&(b.path, b.line, b.column)));
+        }
+        UnresolvedTypes {
+            by_name: report,
+            on_demand: on_demand_report,
+        }
     }
+}
 
-    fn resolve_meta(&self, _meta: &mut Metadata) {}
+type Unresolved<'b> = Vec<(&'b TypePath, TypeUsage, Option<&'b str>)>;
+
+/// Collects the unresolved paths of a type and of its type arguments.
+fn unresolved_paths<'b>(
+    jtype: &'b JType,
+    usage: TypeUsage,
+    member: Option<&'b str>,
+    found: &mut Unresolved<'b>,
+) {
+    let JType::Object { path, generics, .. } = jtype else {
+        return;
+    };
+    if let TypeResolution::None = path.resolved {
+        found.push((path, usage, member));
+    }
+    for invoction in generics.iter().flat_map(|gen| &gen.invoctions) {
+        match invoction {
+            GenericInvoctionPart::Type(jtype) => unresolved_paths(jtype, usage, member, found),
+            GenericInvoctionPart::Wildcard(WildcardBound::None) => {}
+            GenericInvoctionPart::Wildcard(
+                WildcardBound::Extends(list) | WildcardBound::Super(list),
+            ) => {
+                for jtype in list {
+                    unresolved_paths(jtype, usage, member, found);
+                }
+            }
+        }
+    }
+}
+
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line_start = before.rfind('\n').map(|v| v + 1).unwrap_or(0);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+// ----------------------- Package layout
+
+/// The result of [`Project::check_package_layout`].
+#[derive(Debug, Clone, Default)]
+pub struct PackageLayout<'a> {
+    /// Directories the package directories start from, inferred from the
+    /// files whose directory matches their package.
+    pub source_roots: Vec<PathBuf>,
+    /// Files whose directory doesn't match their package, ordered by path.
+    pub mismatches: Vec<PackageMismatch<'a>>,
+}
+
+/// A file whose package doesn't match the directory it is in.
+#[derive(Debug, Clone)]
+pub struct PackageMismatch<'a> {
+    pub path: &'a Path,
+    /// `None` for the default package.
+    pub package: Option<JPath>,
+    /// Where the file belongs, `None` if it isn't under any source root.
+    pub expected: Option<PathBuf>,
+}
+
+impl<'a> std::fmt::Display for PackageMismatch<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let package = match &self.package {
+            Some(package) => format!("package `{}`", package.path),
+            None => "the default package".to_owned(),
+        };
+        writeln!(
+            f,
+            "{BOLD}{YELLOW}warning{RESET}{BOLD}: {package} doesn't match the directory of the file{RESET}"
+        )?;
+        writeln!(f, "  {BLUE}{BOLD}-->{RESET} {}", self.path.display())?;
+        match &self.expected {
+            Some(expected) => writeln!(f, "  expected it in {}", expected.display()),
+            None => writeln!(f, "  it isn't under any detected source root"),
+        }
+    }
+}
+
+impl<'a> std::fmt::Display for PackageLayout<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for mismatch in &self.mismatches {
+            write!(f, "{mismatch}")?;
+        }
+        if !self.mismatches.is_empty() {
+            for root in &self.source_roots {
+                writeln!(f, "note: detected source root {}", root.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Project<'a> {
+    /// Checks that the directory of every file matches its package.
+    ///
+    /// A file whose directory ends with its package names a source root. A
+    /// root nested inside another one is treated as a coincidence when it
+    /// holds files of the outer root, like `src/a` for `src/a/b/B.java` in
+    /// package `b` next to `src/a/A.java` in package `a`, and kept otherwise,
+    /// like `proj/src/main/java` next to `proj/tools/T.java` in package
+    /// `tools`. Every file is then expected in its package directory
+    /// under the innermost root containing it.
+    pub fn check_package_layout(&self) -> PackageLayout<'a> {
+        let mut packages: BTreeMap<&'a Path, Option<&JPath>> = BTreeMap::new();
+        for (class_path, class) in &self.type_map {
+            if class.parent_class.is_some() {
+                continue;
+            }
+            if let Some(&path) = self.files.get(class_path) {
+                packages.insert(path, class.package.as_ref());
+            }
+        }
+
+        let mut candidates: BTreeMap<PathBuf, Vec<&Path>> = BTreeMap::new();
+        for (&path, package) in &packages {
+            if let Some(root) = package.and_then(|package| source_root(path, package)) {
+                candidates.entry(root).or_default().push(path);
+            }
+        }
+        let source_roots: Vec<PathBuf> = candidates
+            .keys()
+            .filter(|root| {
+                !candidates.iter().any(|(other, files)| {
+                    other != *root
+                        && root.starts_with(other)
+                        && files.iter().any(|file| file.starts_with(root))
+                })
+            })
+            .cloned()
+            .collect();
+
+        let mut mismatches = Vec::new();
+        for (&path, package) in &packages {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let root = source_roots
+                .iter()
+                .filter(|root| dir.starts_with(root))
+                .max_by_key(|root| root.components().count());
+            let expected = root.map(|root| {
+                let mut expected = root.clone();
+                expected.extend(package.iter().flat_map(|package| package.path.split('.')));
+                expected
+            });
+            let matches = match &expected {
+                Some(expected) => expected == dir,
+                // nothing to compare a default package file against
+                None => package.is_none(),
+            };
+            if !matches {
+                mismatches.push(PackageMismatch {
+                    path,
+                    package: package.cloned(),
+                    expected,
+                });
+            }
+        }
+
+        PackageLayout {
+            source_roots,
+            mismatches,
+        }
+    }
+}
+
+/// The directory `path` would have to be in for its directory to match
+/// `package`, or `None` if it doesn't match.
+fn source_root(path: &Path, package: &JPath) -> Option<PathBuf> {
+    let mut dir = path.parent()?;
+    for part in package.path.split('.').rev() {
+        if dir.file_name()? != part {
+            return None;
+        }
+        dir = dir.parent()?;
+    }
+    Some(dir.to_path_buf())
+}
+
+// ----------------------- Neighborhood
+
+impl<'a> Project<'a> {
+    /// Collects the classes at most `depth` references away from `center`,
+    /// following extends, implements, permits, field, parameter and return
+    /// types in both directions. Empty if `center` isn't a class of the
+    /// project.
+    pub fn neighborhood(&self, center: &JPath, depth: usize) -> HashSet<ClassPath> {
+        let mut adjacent: HashMap<&JPath, Vec<&JPath>> = HashMap::new();
+        for class in self.type_map.values() {
+            for target in referenced_types(class) {
+                if target != &class.class_path && self.type_map.contains_key(target) {
+                    adjacent.entry(&class.class_path).or_default().push(target);
+                    adjacent.entry(target).or_default().push(&class.class_path);
+                }
+            }
+        }
+
+        let mut found = HashSet::new();
+        if !self.type_map.contains_key(center) {
+            return found;
+        }
+        found.insert(ClassPath(center.clone()));
+        let mut frontier = vec![center];
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for path in frontier {
+                for &adjacent in adjacent.get(path).into_iter().flatten() {
+                    if found.insert(ClassPath(adjacent.clone())) {
+                        next.push(adjacent);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        found
+    }
+
+    /// Copies the given classes into a project of their own. With `stubs`
+    /// the classes they reference but which aren't part of it are added as
+    /// empty [`Project::stubs`], every other class ends up in
+    /// [`Project::omitted`].
+    pub fn subproject(&self, classes: &HashSet<ClassPath>, stubs: bool) -> Project<'a> {
+        let mut stub_paths = HashSet::new();
+        if stubs {
+            for class in classes.iter().filter_map(|path| self.type_map.get(path)) {
+                for target in referenced_types(class) {
+                    if self.type_map.contains_key(target) && !classes.contains(target) {
+                        stub_paths.insert(ClassPath(target.clone()));
+                    }
+                }
+            }
+        }
+
+        let mut sub = Project {
+            sources: self.sources.clone(),
+            ..Default::default()
+        };
+        for path in classes.iter().chain(&stub_paths) {
+            let (Some(class), Some(&file)) = (self.type_map.get(path), self.files.get(path)) else {
+                continue;
+            };
+            let mut class = class.clone();
+            if stub_paths.contains(path) {
+                class.variables.clear();
+                class.functions.clear();
+                class.extends = None;
+                class.implements = None;
+                class.permits = None;
+                if let ClassType::Enum(constants) = &mut class.class_type {
+                    constants.clear();
+                }
+            }
+            if let Some(imports) = self.imports.get(path) {
+                sub.imports.insert(path.clone(), imports.clone());
+            }
+            sub.add_class(file, class);
+        }
+        let sub_files: HashSet<&Path> = sub.files.values().copied().collect();
+        sub.static_imports = self
+            .static_imports
+            .iter()
+            .filter(|import| sub_files.contains(import.file))
+            .cloned()
+            .collect();
+        sub.omitted = self.types.difference(&sub.types).cloned().collect();
+        sub.stubs = stub_paths;
+        sub
+    }
+}
+
+/// The resolved types a class refers to through its supertypes, permitted
+/// subclasses, fields, parameters and return types, type arguments included.
+fn referenced_types<'b>(class: &'b Class) -> Vec<&'b JPath> {
+    fn resolved_paths<'b>(jtype: &'b JType, out: &mut Vec<&'b JPath>) {
+        let JType::Object { path, generics, .. } = jtype else {
+            return;
+        };
+        if let TypeResolution::Some(resolved) = &path.resolved {
+            out.push(resolved);
+        }
+        for invoction in generics.iter().flat_map(|gen| &gen.invoctions) {
+            match invoction {
+                GenericInvoctionPart::Type(jtype) => resolved_paths(jtype, out),
+                GenericInvoctionPart::Wildcard(WildcardBound::None) => {}
+                GenericInvoctionPart::Wildcard(
+                    WildcardBound::Extends(list) | WildcardBound::Super(list),
+                ) => {
+                    for jtype in list {
+                        resolved_paths(jtype, out);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let supertypes = class
+        .extends
+        .iter()
+        .chain(&class.implements)
+        .chain(&class.permits);
+    for jtype in supertypes.flatten() {
+        resolved_paths(jtype, &mut out);
+    }
+    for variable in &class.variables {
+        resolved_paths(&variable.jtype, &mut out);
+    }
+    for function in &class.functions {
+        for param in &function.parameters {
+            resolved_paths(&param.jtype, &mut out);
+        }
+        if let functions::FunctionKind::Regular(jtype)
+        | functions::FunctionKind::AnnotationElement(jtype, _) = &function.kind
+        {
+            resolved_paths(jtype, &mut out);
+        }
+    }
+    out
 }
 
 // ----------------------- Visitor stuff
 
 impl<'a> Project<'a> {
-    pub fn visit<T: Visitor>(visitor: &mut T) -> Result<T::Ok, T::Err> {
+    /// Walks the project in a deterministic order: packages sorted by path,
+    /// the top level classes of each package sorted by path, and every
+    /// class followed by its nested classes. Within a class the fields come
+    /// before the functions, both in declaration order.
+    pub fn visit<T: Visitor>(&self, visitor: &mut T) -> Result<T::Ok, T::Err> {
         visitor.visit_start()?;
+        visitor.visit_pre_meta()?;
 
+        let mut packages: Vec<_> = self.packages.iter().collect();
+        packages.sort_by(|a, b| a.0 .0.path.cmp(&b.0 .0.path));
+        for (package, classes) in packages {
+            visitor.visit_package_start(&package.0)?;
+            let mut top_level: Vec<&Class> = classes
+                .iter()
+                .filter_map(|path| self.type_map.get(path))
+                .filter(|class| {
+                    class
+                        .parent_class
+                        .as_ref()
+                        .is_none_or(|parent| !self.type_map.contains_key(parent))
+                })
+                .collect();
+            top_level.sort_by(|a, b| a.class_path.path.cmp(&b.class_path.path));
+            for class in top_level {
+                self.visit_class(class, visitor)?;
+            }
+            visitor.visit_package_end(&package.0)?;
+        }
+
+        visitor.visit_post_meta()?;
         visitor.visit_end()
     }
+
+    fn visit_class<T: Visitor>(&self, class: &Class, visitor: &mut T) -> Result<(), T::Err> {
+        visitor.visit_pre_class_meta()?;
+        visitor.visit_class(class)?;
+        for variable in &class.variables {
+            visitor.visit_field(class, variable)?;
+        }
+        for function in &class.functions {
+            visitor.visit_function(class, function)?;
+            for parameter in &function.parameters {
+                visitor.visit_parameter(function, parameter)?;
+            }
+        }
+        visitor.visit_class_end(class)?;
+        visitor.visit_post_class_meta()?;
+
+        let mut nested: Vec<&Class> = self
+            .path_resolves
+            .get(&class.class_path)
+            .into_iter()
+            .flatten()
+            .filter_map(|path| self.type_map.get(path))
+            .collect();
+        nested.sort_by(|a, b| a.class_path.path.cmp(&b.class_path.path));
+        for nested in nested {
+            visitor.visit_nested_class(class, nested)?;
+            self.visit_class(nested, visitor)?;
+        }
+        Ok(())
+    }
 }
 
+/// Receives the parts of a [`Project`] from [`Project::visit`]. Every hook
+/// but [`Visitor::visit_end`] does nothing by default.
 pub trait Visitor {
     type Ok;
     type Err;
 
-    fn visit_start(&mut self) -> Result<(), Self::Err>;
+    fn visit_start(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
 
-    fn visit_pre_meta(&mut self) -> Result<(), Self::Err>;
+    fn visit_pre_meta(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
 
-    fn visit_package_start(&mut self, package: &JPath) -> Result<(), Self::Err>;
-    fn visit_pre_class_meta(&mut self) -> Result<(), Self::Err>;
+    /// The default package has an empty path.
+    fn visit_package_start(&mut self, _package: &JPath) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    fn visit_pre_class_meta(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
 
-    fn visit_class(&mut self, class: &Class) -> Result<(), Self::Err>;
+    fn visit_class(&mut self, _class: &Class) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    fn visit_field(&mut self, _class: &Class, _field: &Variable) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    fn visit_function(&mut self, _class: &Class, _function: &Function) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    fn visit_parameter(
+        &mut self,
+        _function: &Function,
+        _parameter: &Parameter,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    /// Called after every member of the class was visited and before its
+    /// nested classes are.
+    fn visit_class_end(&mut self, _class: &Class) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    /// Called right before `nested` itself is visited.
+    fn visit_nested_class(&mut self, _parent: &Class, _nested: &Class) -> Result<(), Self::Err> {
+        Ok(())
+    }
 
-    fn visit_post_class_meta(&mut self) -> Result<(), Self::Err>;
-    fn visit_package_end(&mut self, package: &JPath) -> Result<(), Self::Err>;
+    fn visit_post_class_meta(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
+    fn visit_package_end(&mut self, _package: &JPath) -> Result<(), Self::Err> {
+        Ok(())
+    }
 
-    fn visit_post_meta(&mut self) -> Result<(), Self::Err>;
+    fn visit_post_meta(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
 
     fn visit_end(&mut self) -> Result<Self::Ok, Self::Err>;
 }
@@ -0,0 +1,166 @@
+/// The contents of a source file along with the text that is lexed.
+///
+/// Java translates unicode escapes like `\u0041` into the character they
+/// stand for before lexing (JLS 3.3), so an escape can be part of any token
+/// or even stand for syntax, like `\u0022` for a quote. The translation is
+/// kept next to the original so positions found while lexing can be mapped
+/// back to where they were written.
+#[derive(Debug, Clone)]
+pub struct Source {
+    original: String,
+    translated: Option<Translated>,
+}
+
+#[derive(Debug, Clone)]
+struct Translated {
+    text: String,
+    /// The offsets right after every escape, in the translated text and in
+    /// the original, in ascending order.
+    escapes: Vec<(usize, usize)>,
+}
+
+impl Source {
+    pub fn new(original: String) -> Self {
+        let translated = translate_escapes(&original);
+        Self {
+            original,
+            translated,
+        }
+    }
+
+    /// The source as it was written.
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    /// The source with every unicode escape translated, the text the
+    /// tokenizer should see.
+    pub fn text(&self) -> &str {
+        self.translated
+            .as_ref()
+            .map_or(&self.original, |translated| &translated.text)
+    }
+
+    /// Maps an offset into [`Source::text`] to the same position in
+    /// [`Source::original`].
+    pub fn original_offset(&self, offset: usize) -> usize {
+        let Some(translated) = &self.translated else {
+            return offset;
+        };
+        let after = translated
+            .escapes
+            .partition_point(|&(text, _)| text <= offset);
+        match after.checked_sub(1) {
+            Some(index) => {
+                let (text, original) = translated.escapes[index];
+                original + (offset - text)
+            }
+            None => offset,
+        }
+    }
+
+    /// Maps a range of [`Source::text`] to the range of
+    /// [`Source::original`] it was translated from.
+    pub fn original_range(&self, range: std::ops::Range<usize>) -> std::ops::Range<usize> {
+        // an escape ending the range belongs to it
+        let end = match &self.translated {
+            Some(translated) => translated
+                .escapes
+                .iter()
+                .find(|&&(text, _)| text == range.end)
+                .map_or_else(
+                    || self.original_offset(range.end),
+                    |&(_, original)| original,
+                ),
+            None => range.end,
+        };
+        self.original_offset(range.start)..end
+    }
+}
+
+impl From<String> for Source {
+    fn from(original: String) -> Self {
+        Self::new(original)
+    }
+}
+
+impl From<&str> for Source {
+    fn from(original: &str) -> Self {
+        Self::new(original.to_owned())
+    }
+}
+
+/// Replaces every unicode escape with its character, `None` if there are
+/// none. A backslash only starts an escape when it isn't escaped itself, so
+/// `\\u0041` is left alone. Escapes that don't stand for a character, like
+/// half of a surrogate pair on its own, are kept as written.
+fn translate_escapes(original: &str) -> Option<Translated> {
+    if !original.contains("\\u") {
+        return None;
+    }
+    let bytes = original.as_bytes();
+    let mut translated = Translated {
+        text: String::with_capacity(original.len()),
+        escapes: Vec::new(),
+    };
+    let mut copied = 0;
+    let mut index = 0;
+    // the number of backslashes right in front of `index`
+    let mut backslashes = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'\\' || backslashes % 2 == 1 {
+            backslashes = if bytes[index] == b'\\' {
+                backslashes + 1
+            } else {
+                0
+            };
+            index += 1;
+            continue;
+        }
+        let Some((unit, end)) = escape_at(original, index) else {
+            backslashes += 1;
+            index += 1;
+            continue;
+        };
+        let (char, end) = match char::from_u32(unit) {
+            Some(char) => (char, end),
+            None => {
+                // a high surrogate is only a character with the low one after it
+                let low = escape_at(original, end)
+                    .filter(|_| (0xd800..0xdc00).contains(&unit))
+                    .filter(|&(low, _)| (0xdc00..0xe000).contains(&low));
+                let Some((low, end)) = low else {
+                    backslashes = 0;
+                    index = end;
+                    continue;
+                };
+                let unit = 0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00);
+                (char::from_u32(unit)?, end)
+            }
+        };
+        translated.text.push_str(&original[copied..index]);
+        translated.text.push(char);
+        translated.escapes.push((translated.text.len(), end));
+        copied = end;
+        backslashes = 0;
+        index = end;
+    }
+    translated.text.push_str(&original[copied..]);
+    Some(translated)
+}
+
+/// Reads the escape `\u+XXXX` starting at `start`, returns the UTF-16 code
+/// unit it stands for and where it ends.
+fn escape_at(source: &str, start: usize) -> Option<(u32, usize)> {
+    let rest = source[start..].strip_prefix('\\')?;
+    let digits = rest.trim_start_matches('u');
+    if digits.len() == rest.len() {
+        return None;
+    }
+    let hex = digits.get(..4)?;
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let unit = u32::from_str_radix(hex, 16).ok()?;
+    Some((unit, source.len() - digits.len() + 4))
+}
@@ -15,13 +15,21 @@ pub enum UmlMeta<'a> {
 }
 
 impl<'a> UmlMeta<'a> {
+    /// Parses the text after the `/*UML_` prefix of a metadata comment.
+    ///
+    /// Directives may repeat the `UML_` prefix, so both `/*UML_LINE ...*/`
+    /// and `/*UML_UML_LINE ...*/` are accepted.
     pub fn parse(str: &'a str) -> Self {
-        match str.split_once(' ') {
-            Some(("INNER_CLASS_LINE_NOTE", msg)) => UmlMeta::InnerClassNote(msg),
-            Some(("UML_INNER_CLASS_LINE_P_C", msg)) => UmlMeta::InnerClassLinePC(msg),
-            Some(("UML_RAW_OUTER", msg)) => UmlMeta::RawOuter(msg),
-            Some(("UML_LINE", msg)) => UmlMeta::Line(msg),
-            Some(("HIDE", "")) => UmlMeta::Hide,
+        match str.trim_end().split_once(' ') {
+            Some(("INNER_CLASS_LINE_NOTE" | "UML_INNER_CLASS_LINE_NOTE", msg)) => {
+                UmlMeta::InnerClassNote(msg)
+            }
+            Some(("INNER_CLASS_LINE_P_C" | "UML_INNER_CLASS_LINE_P_C", msg)) => {
+                UmlMeta::InnerClassLinePC(msg)
+            }
+            Some(("RAW_OUTER" | "UML_RAW_OUTER", msg)) => UmlMeta::RawOuter(msg),
+            Some(("LINE" | "UML_LINE", msg)) => UmlMeta::Line(msg),
+            None if matches!(str.trim(), "HIDE" | "UML_HIDE") => UmlMeta::Hide,
             _ => UmlMeta::Invalid(str),
         }
     }
@@ -30,6 +38,11 @@ impl<'a> UmlMeta<'a> {
 #[derive(Debug, Clone, Copy)]
 pub enum Token<'a> {
     Ident(&'a str),
+    /// A string, text block, char, numeric, boolean or `null` literal, as
+    /// written in the source.
+    Literal(&'a str),
+    /// Any operator without a token of its own, like `==`, `->` or `::`.
+    Operator(&'a str),
     Comment(&'a str),
     Annotation(&'a str),
 
@@ -82,12 +95,40 @@ pub enum Token<'a> {
     Ignore,
 }
 
-#[derive(Clone, Copy)]
-pub struct Tokenizer<'a>(&'a str, usize);
+/// Splits Java source into tokens.
+///
+/// Unicode escapes are expected to be translated already (JLS 3.3), lex
+/// [`Source::text`](super::source::Source::text) rather than the file as it
+/// was written. Escapes that couldn't be translated, like half of a
+/// surrogate pair, are accepted in identifiers and literals as written.
+#[derive(Clone)]
+pub struct Tokenizer<'a> {
+    data: &'a str,
+    len: usize,
+    doc: Option<&'a str>,
+    /// Every `/** ... */` comment seen so far, keyed by the start of the
+    /// token that follows it.
+    docs: Vec<(usize, &'a str)>,
+}
 
 impl<'a> Tokenizer<'a> {
     pub fn new(data: &'a str) -> Self {
-        Self(data, data.len())
+        Self {
+            data,
+            len: data.len(),
+            doc: None,
+            docs: Vec::new(),
+        }
+    }
+
+    /// The documentation comment right in front of the token starting at
+    /// `start`, if it was already lexed. Regular comments in between are
+    /// skipped over.
+    pub fn doc_comment(&self, start: usize) -> Option<&'a str> {
+        self.docs
+            .binary_search_by_key(&start, |(start, _)| *start)
+            .ok()
+            .map(|index| self.docs[index].1)
     }
 }
 
@@ -126,7 +167,13 @@ impl<'a> Iterator for Tokenizer<'a> {
             "throws" => Token::Throws,
             "extends" => Token::Extends,
             "super" => Token::Super,
-            "@[a-zA-Z0-9_]*" => Token::Annotation(&text["@".len()..]),
+            "true" => Token::Literal(text),
+            "false" => Token::Literal(text),
+            "null" => Token::Literal(text),
+
+            // identifiers can hold any letter, `$` and the unicode escapes
+            // that weren't translated
+            "@(([^\u{0}-#%-@[-^`{-\u{a0}\u{2000}-\u{200b}\u{2028}\u{2029}\u{3000}\u{feff}]|\\\\u+[0-9a-fA-F][0-9a-fA-F][0-9a-fA-F][0-9a-fA-F])([^\u{0}-#%-/:-@[-^`{-\u{a0}\u{2000}-\u{200b}\u{2028}\u{2029}\u{3000}\u{feff}]|\\\\u+[0-9a-fA-F][0-9a-fA-F][0-9a-fA-F][0-9a-fA-F])*)?" => Token::Annotation(&text["@".len()..]),
 
 
             r#"/[*]UML_(~(.*[*]/.*))[*]/"# => Token::UmlMeta(UmlMeta::parse(&text["/*UML_".len()..text.len()-"*/".len()])),
@@ -148,28 +195,84 @@ impl<'a> Iterator for Tokenizer<'a> {
             "\\]" => Token::RBracket,
             "=" => Token::Equals,
 
-
-            r#"[a-zA-Z_][a-zA-Z0-9_]*"# => Token::Ident(text),
+            // operators that never appear in a declaration. `>>` and `>>>`
+            // are left as separate `>` so nested type arguments still close
+            "==" => Token::Operator(text),
+            "!=" => Token::Operator(text),
+            "<=" => Token::Operator(text),
+            ">=" => Token::Operator(text),
+            "!" => Token::Operator(text),
+            "\\~" => Token::Operator(text),
+            ":" => Token::Operator(text),
+            "::" => Token::Operator(text),
+            "->" => Token::Operator(text),
+            "\\&\\&" => Token::Operator(text),
+            "\\|\\|" => Token::Operator(text),
+            "\\+\\+" => Token::Operator(text),
+            "--" => Token::Operator(text),
+            "\\+" => Token::Operator(text),
+            "-" => Token::Operator(text),
+            "/" => Token::Operator(text),
+            "\\|" => Token::Operator(text),
+            "^" => Token::Operator(text),
+            "%" => Token::Operator(text),
+            "<<" => Token::Operator(text),
+            "\\+=" => Token::Operator(text),
+            "-=" => Token::Operator(text),
+            "\\*=" => Token::Operator(text),
+            "/=" => Token::Operator(text),
+            "\\&=" => Token::Operator(text),
+            "\\|=" => Token::Operator(text),
+            "^=" => Token::Operator(text),
+            "%=" => Token::Operator(text),
+            "<<=" => Token::Operator(text),
+            ">>=" => Token::Operator(text),
+            ">>>=" => Token::Operator(text),
+
+
+            "([^\u{0}-#%-@[-^`{-\u{a0}\u{2000}-\u{200b}\u{2028}\u{2029}\u{3000}\u{feff}]|\\\\u+[0-9a-fA-F][0-9a-fA-F][0-9a-fA-F][0-9a-fA-F])([^\u{0}-#%-/:-@[-^`{-\u{a0}\u{2000}-\u{200b}\u{2028}\u{2029}\u{3000}\u{feff}]|\\\\u+[0-9a-fA-F][0-9a-fA-F][0-9a-fA-F][0-9a-fA-F])*" => Token::Ident(text),
 
             // comments
+            r#"/[*][*](~(.*[*]/.*))[*]/"# => Token::Comment(text),
             r#"/[*](~(.*[*]/.*))[*]/"# => Token::Ignore,
             r#"//[^\n]*"# => Token::Ignore,
-            // string / chars
-            r#""([^"]|\\")*""# => Token::Ignore,
-            r#"'"([^']|\\')*'"# => Token::Ignore,
+
+            // strings, text blocks and chars. A text block can't hold three
+            // unescaped quotes in a row before its closing delimiter
+            r#""([^"\\\n]|\\[^\n])*""# => Token::Literal(text),
+            r#""""([^"\\]|\\.|"[^"\\]|"\\.|""[^"\\]|""\\.)*("|"")?""""# => Token::Literal(text),
+            r#"'([^'\\\n]|\\[^\n])+'"# => Token::Literal(text),
+
+            // integers, in decimal, octal, hex or binary
+            r#"[0-9][0-9_]*[lL]?"# => Token::Literal(text),
+            r#"0[xX][0-9a-fA-F_]+[lL]?"# => Token::Literal(text),
+            r#"0[bB][01_]+[lL]?"# => Token::Literal(text),
+            // decimal and hex floating point
+            r#"[0-9][0-9_]*\.([0-9][0-9_]*)?([eE][\-+]?[0-9][0-9_]*)?[fFdD]?"# => Token::Literal(text),
+            r#"\.[0-9][0-9_]*([eE][\-+]?[0-9][0-9_]*)?[fFdD]?"# => Token::Literal(text),
+            r#"[0-9][0-9_]*[eE][\-+]?[0-9][0-9_]*[fFdD]?"# => Token::Literal(text),
+            r#"[0-9][0-9_]*[fFdD]"# => Token::Literal(text),
+            r#"0[xX][0-9a-fA-F_]*\.?[0-9a-fA-F_]*[pP][\-+]?[0-9][0-9_]*[fFdD]?"# => Token::Literal(text),
 
             r#"."# => Token::Ignore,
         }
 
         loop {
-            if let Some((tok, next)) = next_token(self.0) {
-                let range = self.1 - self.0.len()..self.1 - next.len();
-                self.0 = next;
+            if let Some((tok, next)) = next_token(self.data) {
+                let range = self.len - self.data.len()..self.len - next.len();
+                self.data = next;
                 match tok {
-                    Token::Comment(_) | Token::Ignore => continue,
+                    Token::Comment(comment) => {
+                        self.doc = Some(comment);
+                        continue;
+                    }
+                    Token::Ignore => continue,
                     _ => {}
                 }
 
+                if let Some(doc) = self.doc.take() {
+                    self.docs.push((range.start, doc));
+                }
                 break Some((tok, range));
             } else {
                 break None;
@@ -198,6 +301,11 @@ impl<T: Iterator> Peek2able<T> for T {
 }
 
 impl<T: Iterator> Peek2<T> {
+    /// The wrapped iterator, which may already be up to two items ahead.
+    pub fn get_ref(&self) -> &T {
+        &self.iter
+    }
+
     pub fn peek(&mut self) -> Option<&T::Item> {
         self.peek[0]
             .get_or_insert_with(|| self.iter.next())
@@ -1,2 +1,63 @@
+pub mod dot;
 pub mod java;
+pub mod mermaid;
 pub mod plantuml;
+pub mod uml;
+
+/// Parses and resolves `files`, each given as its path and contents, and
+/// returns what `write` generates from them.
+#[cfg(test)]
+fn render(
+    files: &[(&str, &str)],
+    write: impl FnOnce(&mut Vec<u8>, &java::project::Project) -> std::io::Result<()>,
+) -> String {
+    use java::project::{Files, Project};
+
+    let mut sources = Files::new();
+    for (path, contents) in files {
+        sources.files.insert(path.into(), (*contents).into());
+    }
+    let (mut project, errors) = Project::parse_all(&sources);
+    assert!(errors.is_empty());
+    project.resolve_imports();
+    project.resolve_types();
+
+    let mut out = Vec::new();
+    write(&mut out, &project).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_deterministic_output() {
+    let mut sources = Vec::new();
+    for (package, class) in [("a", "Z"), ("a", "Y"), ("b", "X"), ("c", "W"), ("", "V")] {
+        let header = if package.is_empty() {
+            String::new()
+        } else {
+            format!("package {package};")
+        };
+        sources.push((
+            format!("{package}/{class}.java"),
+            format!("{header} public class {class} extends Base implements Runnable {{ int a; class Inner {{}} }}"),
+        ));
+    }
+    let files: Vec<_> = sources
+        .iter()
+        .map(|(path, contents)| (path.as_str(), contents.as_str()))
+        .collect();
+
+    // every project gets new hash maps with their own random order
+    let generate = || {
+        [
+            render(&files, |out, project| {
+                plantuml::PlantUmlGen::new(out, project, Default::default()).write()
+            }),
+            render(&files, |out, project| mermaid::MermaidGen::new(out, project).write()),
+            render(&files, |out, project| dot::DotGen::new(out, project).write()),
+        ]
+    };
+    let first = generate();
+    for _ in 0..8 {
+        assert_eq!(generate(), first);
+    }
+}
@@ -1,30 +1,442 @@
-use std::{fs::File, io::BufWriter};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
 
-use java_uml::{java::project, plantuml::PlantUmlGen};
+use java_uml::{
+    dot::DotGen,
+    java::{
+        ast::{JPath, Visibility},
+        project,
+    },
+    mermaid::MermaidGen,
+    plantuml::{PlantUmlGen, PlantUmlOptions},
+};
 
-pub mod java;
+const USAGE: &str = "Usage: java_uml [OPTIONS] <PATH>...
+
+Generates a class diagram from every .java file found under the given paths.
+
+Options:
+  -o, --output <FILE>            Write the diagram to FILE, `-` for stdout [default: output.txt]
+  -f, --format <FORMAT>          plantuml, mermaid or dot [default: plantuml]
+      --min-visibility <VIS>     Only show members at least this visible:
+                                 public, protected, package or private [default: private]
+      --no-fields                Don't show fields
+      --no-methods               Don't show methods or constructors
+      --no-constructors          Don't show constructors
+      --no-accessors             Don't show getters and setters
+      --no-static                Don't show static fields and methods
+      --max-members <N>          Show at most N fields and methods per class (plantuml only)
+      --include <GLOB>           Only show classes whose path or name matches GLOB, can be
+                                 repeated (plantuml only)
+      --exclude <GLOB>           Hide classes whose path or name matches GLOB, can be
+                                 repeated (plantuml only)
+      --notes                    Add a note with the Javadoc summary of each class (plantuml only)
+      --member-notes             Add a note with the Javadoc summaries of each class's fields
+                                 and methods (plantuml only)
+      --note-max-len <N>         Cut Javadoc summaries in notes short after N characters
+      --note-width <N>           Wrap notes onto lines of at most N characters
+      --focus <CLASS>            Only show the classes around CLASS, given by its full path or
+                                 by its name if that is unique
+      --depth <N>                How many references away from --focus to go [default: 1]
+      --stubs                    Show the classes just outside of --depth as empty stubs
+      --duplicates <POLICY>      Which declaration to keep when files declare the same class:
+                                 first, last or error [default: first]
+      --check-packages           Warn about files whose package doesn't match their directory
+      --report-unresolved        List every type that couldn't be resolved on stderr
+  -h, --help                     Print this help
+
+Exit status:
+  0  the diagram was written
+  1  one or more files had syntax errors, the diagram was still written
+     without the parts that failed to parse
+  2  the command line was invalid
+  3  reading the sources or writing the diagram failed
+  4  files declare the same class and --duplicates is error, nothing was written
+";
+
+const EXIT_OK: u8 = 0;
+const EXIT_PARSE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_IO: u8 = 3;
+const EXIT_DUPLICATE: u8 = 4;
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    PlantUml,
+    Mermaid,
+    Dot,
+}
+
+#[derive(Debug)]
+struct Args {
+    inputs: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    format: Format,
+    filter: project::MemberFilter,
+    max_members: Option<usize>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    class_notes: bool,
+    member_notes: bool,
+    note_max_len: Option<usize>,
+    note_width: Option<usize>,
+    focus: Option<String>,
+    depth: usize,
+    stubs: bool,
+    duplicates: project::DuplicatePolicy,
+    check_packages: bool,
+    report_unresolved: bool,
+}
+
+#[derive(Debug)]
+enum Command {
+    Run(Args),
+    Help,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args {
+        inputs: Vec::new(),
+        output: Some("output.txt".into()),
+        format: Format::PlantUml,
+        filter: project::MemberFilter::new(),
+        max_members: None,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        class_notes: false,
+        member_notes: false,
+        note_max_len: None,
+        note_width: None,
+        focus: None,
+        depth: 1,
+        stubs: false,
+        duplicates: project::DuplicatePolicy::FirstWins,
+        check_packages: false,
+        report_unresolved: false,
+    };
+
+    while let Some(arg) = args.next() {
+        // support both `--flag value` and `--flag=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_owned(), Some(value.to_owned()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{name} expects a value"))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-o" | "--output" => {
+                let output = value("--output")?;
+                parsed.output = if output == "-" {
+                    None
+                } else {
+                    Some(output.into())
+                };
+            }
+            "-f" | "--format" => {
+                parsed.format = match value("--format")?.as_str() {
+                    "plantuml" | "puml" => Format::PlantUml,
+                    "mermaid" => Format::Mermaid,
+                    "dot" | "graphviz" => Format::Dot,
+                    other => return Err(format!("unknown format `{other}`")),
+                }
+            }
+            "--min-visibility" => {
+                parsed.filter.min_visibility = match value("--min-visibility")?.as_str() {
+                    "public" => Visibility::Public,
+                    "protected" => Visibility::Protected,
+                    "package" => Visibility::None,
+                    "private" => Visibility::Private,
+                    other => return Err(format!("unknown visibility `{other}`")),
+                }
+            }
+            "--no-fields" => parsed.filter.fields = false,
+            "--no-methods" => {
+                parsed.filter.methods = false;
+                parsed.filter.constructors = false;
+            }
+            "--no-constructors" => parsed.filter.constructors = false,
+            "--no-accessors" => parsed.filter.accessors = false,
+            "--no-static" => parsed.filter.statics = false,
+            "--max-members" => {
+                let max = value("--max-members")?;
+                parsed.max_members = Some(
+                    max.parse()
+                        .map_err(|_| format!("invalid member count `{max}`"))?,
+                );
+            }
+            "--notes" => parsed.class_notes = true,
+            "--member-notes" => parsed.member_notes = true,
+            "--note-max-len" => {
+                let max = value("--note-max-len")?;
+                parsed.note_max_len = Some(
+                    max.parse()
+                        .map_err(|_| format!("invalid note length `{max}`"))?,
+                );
+            }
+            "--note-width" => {
+                let width = value("--note-width")?;
+                parsed.note_width = Some(
+                    width
+                        .parse()
+                        .map_err(|_| format!("invalid note width `{width}`"))?,
+                );
+            }
+            "--focus" => parsed.focus = Some(value("--focus")?),
+            "--depth" => {
+                let depth = value("--depth")?;
+                parsed.depth = depth
+                    .parse()
+                    .map_err(|_| format!("invalid depth `{depth}`"))?;
+            }
+            "--stubs" => parsed.stubs = true,
+            "--include" => parsed.include.push(value("--include")?),
+            "--exclude" => parsed.exclude.push(value("--exclude")?),
+            "--duplicates" => {
+                parsed.duplicates = match value("--duplicates")?.as_str() {
+                    "first" => project::DuplicatePolicy::FirstWins,
+                    "last" => project::DuplicatePolicy::LastWins,
+                    "error" => project::DuplicatePolicy::Error,
+                    other => return Err(format!("unknown duplicate policy `{other}`")),
+                }
+            }
+            "--check-packages" => parsed.check_packages = true,
+            "--report-unresolved" => parsed.report_unresolved = true,
+            "--" => parsed.inputs.extend(args.by_ref().map(PathBuf::from)),
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option `{flag}`"))
+            }
+            _ => parsed.inputs.push(arg.into()),
+        }
+    }
+
+    if parsed.inputs.is_empty() {
+        return Err("expected at least one path to a project".into());
+    }
+    Ok(Command::Run(parsed))
+}
+
+fn main() -> ExitCode {
+    ExitCode::from(run(std::env::args().skip(1)))
+}
+
+/// Runs the command line `args` and returns the exit status.
+fn run(args: impl Iterator<Item = String>) -> u8 {
+    let args = match parse_args(args) {
+        Ok(Command::Run(args)) => args,
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return EXIT_OK;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return EXIT_USAGE;
+        }
+    };
 
-fn main() {
     let mut files = project::Files::new();
-    files
-        .load_dir(std::env::args().nth(1).expect("Expected path to project"))
-        .unwrap();
+    for input in &args.inputs {
+        if let Err(err) = files.load_dir(input) {
+            eprintln!("error: {err}");
+            return EXIT_IO;
+        }
+    }
 
-    let mut project = match project::Project::parse_all(&files) {
-        Ok(ok) => ok,
-        Err(errs) => {
-            for err in errs {
-                println!("{err}");
+    let (mut project, errors) = match project::Project::parse_all_with(&files, args.duplicates) {
+        Ok(parsed) => parsed,
+        Err(duplicates) => {
+            for err in &duplicates.errors {
+                eprintln!("{err}");
             }
-            return;
+            eprint!("{duplicates}");
+            return EXIT_DUPLICATE;
         }
     };
+    for err in &errors {
+        eprintln!("{err}");
+    }
+    for duplicate in &project.duplicates {
+        eprint!("{duplicate}");
+    }
+    if args.check_packages {
+        eprint!("{}", project.check_package_layout());
+    }
 
     project.resolve_imports();
     project.resolve_types();
-    let file = File::create("output.txt").expect("Failed to create output file");
-    let mut writter = BufWriter::new(file);
-    PlantUmlGen::new(&mut writter, &project)
-        .write()
-        .expect("Failed to create UML");
+    if args.report_unresolved {
+        eprint!("{}", project.unresolved_types());
+    }
+    if let Some(focus) = &args.focus {
+        let center = match find_class(&project, focus) {
+            Ok(center) => center,
+            Err(err) => {
+                eprintln!("error: {err}");
+                return EXIT_USAGE;
+            }
+        };
+        let classes = project.neighborhood(&center, args.depth);
+        project = project.subproject(&classes, args.stubs);
+    }
+    if !matches!(args.format, Format::PlantUml) {
+        project.retain_members(&args.filter);
+    }
+
+    let result = match &args.output {
+        Some(path) => File::create(path).and_then(|file| write(&args, file, &project)),
+        None => write(&args, std::io::stdout().lock(), &project),
+    };
+    if let Err(err) = result {
+        match &args.output {
+            Some(path) => eprintln!("error: failed to write {}: {err}", path.display()),
+            None => eprintln!("error: failed to write diagram: {err}"),
+        }
+        return EXIT_IO;
+    }
+
+    if errors.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_PARSE
+    }
+}
+
+/// Finds a class by its full path, or by its name relative to its package.
+fn find_class(project: &project::Project, name: &str) -> Result<JPath, String> {
+    let path = JPath { path: name.into() };
+    if project.type_map.contains_key(&path) {
+        return Ok(path);
+    }
+    let mut matches: Vec<&JPath> = project
+        .type_map
+        .values()
+        .filter(|class| {
+            let package = class.package.as_ref().map_or("", |p| p.path.as_str());
+            class
+                .class_path
+                .path
+                .strip_prefix(package)
+                .map(|rest| rest.trim_start_matches('.'))
+                == Some(name)
+        })
+        .map(|class| &class.class_path)
+        .collect();
+    matches.sort_by(|a, b| a.path.cmp(&b.path));
+    match matches.as_slice() {
+        [] => Err(format!("no class `{name}` in the project")),
+        [path] => Ok((*path).clone()),
+        paths => Err(format!(
+            "`{name}` is ambiguous, it could be any of {}",
+            paths
+                .iter()
+                .map(|path| path.path.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn write(args: &Args, out: impl Write, project: &project::Project) -> std::io::Result<()> {
+    let mut writter = BufWriter::new(out);
+    match args.format {
+        Format::PlantUml => {
+            let options = PlantUmlOptions {
+                members: args.filter,
+                include: args.include.clone(),
+                exclude: args.exclude.clone(),
+                max_members: args.max_members,
+                class_notes: args.class_notes,
+                member_notes: args.member_notes,
+                note_max_len: args.note_max_len,
+                note_width: args.note_width,
+            };
+            PlantUmlGen::new(&mut writter, project, options).write()?
+        }
+        Format::Mermaid => MermaidGen::new(&mut writter, project).write()?,
+        Format::Dot => DotGen::new(&mut writter, project).write()?,
+    }
+    writter.flush()
+}
+
+#[cfg(test)]
+fn parse(line: &[&str]) -> Result<Command, String> {
+    parse_args(line.iter().map(|arg| arg.to_string()))
+}
+
+#[cfg(test)]
+fn parse_run(line: &[&str]) -> Args {
+    match parse(line) {
+        Ok(Command::Run(args)) => args,
+        other => panic!("expected a run, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_args() {
+    let args = parse_run(&["src"]);
+    assert_eq!(args.inputs, [PathBuf::from("src")]);
+    assert_eq!(args.output, Some("output.txt".into()));
+    assert!(matches!(args.format, Format::PlantUml));
+
+    let args = parse_run(&[
+        "a",
+        "--format=mermaid",
+        "-o",
+        "out.mmd",
+        "--min-visibility",
+        "protected",
+        "--no-fields",
+        "--max-members",
+        "3",
+        "--include",
+        "x.*",
+        "--include=y.*",
+        "--duplicates",
+        "error",
+        "b",
+        "--",
+        "--c",
+    ]);
+    assert_eq!(args.inputs, [PathBuf::from("a"), "b".into(), "--c".into()]);
+    assert_eq!(args.output, Some("out.mmd".into()));
+    assert!(matches!(args.format, Format::Mermaid));
+    assert_eq!(args.filter.min_visibility, Visibility::Protected);
+    assert!(!args.filter.fields);
+    assert!(args.filter.methods);
+    assert_eq!(args.max_members, Some(3));
+    assert_eq!(args.include, ["x.*", "y.*"]);
+    assert_eq!(args.duplicates, project::DuplicatePolicy::Error);
+
+    let args = parse_run(&["-o", "-", "-f", "dot", "src"]);
+    assert_eq!(args.output, None);
+    assert!(matches!(args.format, Format::Dot));
+
+    assert!(matches!(parse(&["src", "--help"]), Ok(Command::Help)));
+    assert_eq!(run(["-h".to_owned()].into_iter()), EXIT_OK);
+}
+
+#[test]
+fn test_parse_args_invalid() {
+    for line in [
+        &["--bogus", "src"][..],
+        &["src", "--format", "svg"],
+        &["src", "--max-members", "many"],
+        &["src", "--output"],
+        &[],
+    ] {
+        assert!(parse(line).is_err(), "{line:?} should be rejected");
+        let line = line.iter().map(|arg| arg.to_string());
+        assert_eq!(run(line), EXIT_USAGE);
+    }
 }
@@ -0,0 +1,314 @@
+use crate::{
+    java::{
+        ast::{
+            class::{Class, ClassType},
+            functions::{Function, FunctionKind},
+            types::{JType, TypeResolution},
+            variable::Variable,
+            JPath, Visibility,
+        },
+        project::{Project, Visitor},
+    },
+    uml::{self, Pass, TypeFormat},
+};
+
+type Result = std::io::Result<()>;
+
+/// Mermaid writes type arguments between `~`, as in `List~String~`.
+const TYPES: TypeFormat = TypeFormat {
+    generics: ("~", "~"),
+    arguments: ",",
+    list: " & ",
+};
+
+/// Writes a Mermaid `classDiagram`, which GitHub and GitLab render inside
+/// markdown code blocks.
+///
+/// Mermaid identifiers can't contain dots, so every class is declared with
+/// an identifier derived from its full path and labelled with its name
+/// relative to its package.
+pub struct MermaidGen<'a, T: std::io::Write> {
+    java: &'a Project<'a>,
+    out: &'a mut T,
+    pass: Pass,
+    indent: &'static str,
+}
+
+impl<'a, T: std::io::Write> MermaidGen<'a, T> {
+    pub fn new(out: &'a mut T, java: &'a Project<'a>) -> Self {
+        Self {
+            java,
+            out,
+            pass: Pass::Classes,
+            indent: "",
+        }
+    }
+
+    pub fn write(&mut self) -> Result {
+        uml::visit_passes(self.java, self)
+    }
+
+    fn write_edge(&mut self, class: &Class, supertype: &JType, arrow: &str) -> Result {
+        let JType::Object { path, .. } = supertype else {
+            return Ok(());
+        };
+        match &path.resolved {
+            TypeResolution::Generic => return Ok(()),
+            TypeResolution::Some(resolved) if self.java.omitted.contains(resolved) => return Ok(()),
+            TypeResolution::Some(resolved) => match self.java.type_map.get(resolved) {
+                Some(target) if self.java.is_hidden(target) => return Ok(()),
+                Some(target) => self.write_id(&target.class_path)?,
+                None => self.write_id(&JPath {
+                    path: path.origional.last().to_owned(),
+                })?,
+            },
+            TypeResolution::None => self.write_id(&JPath {
+                path: path.origional.last().to_owned(),
+            })?,
+        }
+        self.out.write_all(arrow.as_bytes())?;
+        self.write_id(&class.class_path)?;
+        self.out.write_all("\n".as_bytes())
+    }
+
+    fn write_id(&mut self, path: &JPath) -> Result {
+        let id: String = path
+            .path
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        self.out.write_all(id.as_bytes())
+    }
+
+    fn write_class(&mut self, class: &Class, indent: &str) -> Result {
+        self.out.write_all(indent.as_bytes())?;
+        self.out.write_all("class ".as_bytes())?;
+        self.write_id(&class.class_path)?;
+
+        if let Some(generics) = &class.generics {
+            self.out.write_all("~".as_bytes())?;
+            for (index, gen) in generics.definitions.iter().enumerate() {
+                self.out.write_all(gen.name.as_bytes())?;
+                if index != generics.definitions.len() - 1 {
+                    self.out.write_all(",".as_bytes())?;
+                }
+            }
+            self.out.write_all("~".as_bytes())?;
+        }
+
+        let name = match &class.package {
+            Some(package) => class
+                .class_path
+                .path
+                .trim_start_matches(&package.path)
+                .trim_start_matches('.'),
+            None => class.class_path.path.as_str(),
+        };
+        self.out.write_all("[\"".as_bytes())?;
+        self.out.write_all(name.as_bytes())?;
+        self.out.write_all("\"] {\n".as_bytes())?;
+
+        let stereotype = if self.java.stubs.contains(&class.class_path) {
+            Some("stub")
+        } else {
+            uml::stereotype(class)
+        };
+        if let Some(stereotype) = stereotype {
+            self.out.write_all(indent.as_bytes())?;
+            self.out.write_all("  <<".as_bytes())?;
+            self.out.write_all(stereotype.as_bytes())?;
+            self.out.write_all(">>\n".as_bytes())?;
+        }
+
+        if let ClassType::Enum(enums) = &class.class_type {
+            for name in enums {
+                self.out.write_all(indent.as_bytes())?;
+                self.out.write_all("  ".as_bytes())?;
+                self.out.write_all(name.as_bytes())?;
+                self.out.write_all("\n".as_bytes())?;
+            }
+        }
+
+        for variable in &class.variables {
+            if variable.meta.hidden {
+                continue;
+            }
+            self.out.write_all(indent.as_bytes())?;
+            self.visit_variable(class, variable)?;
+        }
+
+        for function in &class.functions {
+            if function.meta.hidden {
+                continue;
+            }
+            self.out.write_all(indent.as_bytes())?;
+            self.visit_function(class, function)?;
+        }
+
+        self.out.write_all(indent.as_bytes())?;
+        self.out.write_all("}\n".as_bytes())
+    }
+
+    fn write_visibility(&mut self, vis: Visibility) -> Result {
+        self.out.write_all(uml::visibility(vis).as_bytes())
+    }
+
+    fn visit_variable(&mut self, class: &Class, variable: &Variable) -> Result {
+        self.out.write_all("  ".as_bytes())?;
+        self.write_visibility(class.member_visibility(variable.visibility))?;
+        self.visit_type(&variable.jtype)?;
+        self.out.write_all(" ".as_bytes())?;
+        self.out.write_all(variable.name.as_bytes())?;
+        if variable.modifiers.m_static() {
+            self.out.write_all("$".as_bytes())?;
+        }
+        self.out.write_all("\n".as_bytes())
+    }
+
+    fn visit_function(&mut self, class: &Class, function: &Function) -> Result {
+        self.out.write_all("  ".as_bytes())?;
+        self.write_visibility(class.member_visibility(function.visibility))?;
+        self.out.write_all(function.name.as_bytes())?;
+
+        self.out.write_all("(".as_bytes())?;
+        let parameters: Vec<_> = function
+            .parameters
+            .iter()
+            .filter(|param| !param.meta.hidden)
+            .collect();
+        for (index, param) in parameters.iter().enumerate() {
+            self.visit_type(&param.jtype)?;
+            if param.vargs {
+                self.out.write_all("... ".as_bytes())?;
+            } else {
+                self.out.write_all(" ".as_bytes())?;
+            }
+            self.out.write_all(param.name.as_bytes())?;
+            if index != parameters.len() - 1 {
+                self.out.write_all(", ".as_bytes())?;
+            }
+        }
+        self.out.write_all(")".as_bytes())?;
+
+        if function.modifiers.m_static() {
+            self.out.write_all("$".as_bytes())?;
+        } else if function.modifiers.m_abstract() {
+            self.out.write_all("*".as_bytes())?;
+        }
+
+        match &function.kind {
+            FunctionKind::Regular(ret) | FunctionKind::AnnotationElement(ret, _) => {
+                self.out.write_all(" ".as_bytes())?;
+                self.visit_type(ret)?;
+            }
+            FunctionKind::Constructor | FunctionKind::CompactConstructor => {}
+        }
+        self.out.write_all("\n".as_bytes())
+    }
+
+    fn visit_type(&mut self, jtype: &JType) -> Result {
+        let mut str = String::new();
+        TYPES.write(jtype, &uml::written_name, &mut str);
+        self.out.write_all(str.as_bytes())
+    }
+}
+
+impl<'a, T: std::io::Write> uml::PassVisitor for MermaidGen<'a, T> {
+    fn set_pass(&mut self, pass: Pass) {
+        self.pass = pass;
+    }
+}
+
+impl<'a, T: std::io::Write> Visitor for MermaidGen<'a, T> {
+    type Ok = ();
+    type Err = std::io::Error;
+
+    fn visit_start(&mut self) -> Result {
+        if self.pass != Pass::Classes {
+            return Ok(());
+        }
+        self.out.write_all("classDiagram\n".as_bytes())
+    }
+
+    fn visit_package_start(&mut self, package: &JPath) -> Result {
+        if self.pass != Pass::Classes || package.path.is_empty() {
+            self.indent = "";
+            return Ok(());
+        }
+        self.indent = "  ";
+        self.out.write_all("namespace ".as_bytes())?;
+        self.write_id(package)?;
+        self.out.write_all(" {\n".as_bytes())
+    }
+
+    fn visit_class(&mut self, class: &Class) -> Result {
+        if self.java.is_hidden(class) {
+            return Ok(());
+        }
+        match self.pass {
+            Pass::Classes => self.write_class(class, self.indent),
+            Pass::Relations => {
+                for extends in class.extends.iter().flatten() {
+                    self.write_edge(class, extends, " <|-- ")?;
+                }
+                for implements in class.implements.iter().flatten() {
+                    self.write_edge(class, implements, " <|.. ")?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn visit_package_end(&mut self, package: &JPath) -> Result {
+        if self.pass != Pass::Classes || package.path.is_empty() {
+            return Ok(());
+        }
+        self.out.write_all("}\n".as_bytes())
+    }
+
+    fn visit_end(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_mermaid() {
+    let files = [
+        (
+            "shapes/Shape.java",
+            r#"
+        package shapes;
+
+        public abstract class Shape<T> implements Comparable {
+            protected static int count;
+            public abstract double area();
+        }
+        "#,
+        ),
+        (
+            "shapes/Circle.java",
+            r#"
+        package shapes;
+
+        import java.util.List;
+
+        public class Circle extends Shape<Circle> {
+            private List<Double> radii;
+            public static Circle unit() { return null; }
+        }
+        "#,
+        ),
+    ];
+
+    let out = crate::render(&files, |out, project| MermaidGen::new(out, project).write());
+
+    assert!(out.starts_with("classDiagram\n"));
+    assert!(out.contains("namespace shapes {\n"));
+    assert!(out.contains("  class shapes_Shape~T~[\"Shape\"] {\n    <<abstract>>\n"));
+    assert!(out.contains("    #int count$\n"));
+    assert!(out.contains("    +area()* double\n"));
+    assert!(out.contains("    -List~Double~ radii\n"));
+    assert!(out.contains("    +unit()$ Circle\n"));
+    assert!(out.contains("shapes_Shape <|-- shapes_Circle\n"));
+    assert!(out.contains("Comparable <|.. shapes_Shape\n"));
+}
@@ -1,82 +1,301 @@
-use crate::java::{
-    ast::{
-        class::{Class, ClassType},
-        functions::{Function, FunctionKind},
-        generics::{GenericInvoctionPart, WildcardBound},
-        types::{JType, Primitive, TypePath, TypeResolution},
-        variable::Variable,
-        JPath, Visibility,
+use crate::{
+    java::{
+        ast::{
+            class::{Class, ClassType},
+            functions::{Function, FunctionKind},
+            generics::{GenericInvoctionPart, WildcardBound},
+            types::{JType, TypePath, TypeResolution},
+            variable::Variable,
+            ElementValue, JPath, Metadata, Visibility,
+        },
+        project::{MemberFilter, Project, Visitor},
+        tokenizer::UmlMeta,
     },
-    project::{ClassPath, Project},
+    uml::{self, Pass, TypeFormat},
 };
 
 type Result = std::io::Result<()>;
 
+const TYPES: TypeFormat = TypeFormat {
+    generics: ("<", ">"),
+    arguments: ", ",
+    list: ", ",
+};
+
+/// The name a member's class type is written with.
+fn class_type_name<'b>(java: &Project, jtype: &'b TypePath) -> &'b str {
+    match &jtype.resolved {
+        TypeResolution::Some(full_path) => match java.type_map.get(full_path) {
+            Some(class) => match &class.package {
+                Some(package) => jtype.origional.path.trim_end_matches(&package.path),
+                None => &jtype.origional.path,
+            },
+            None => &jtype.origional.path,
+        },
+        TypeResolution::None | TypeResolution::Generic => &jtype.origional.path,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Multiplicity {
+    One,
+    Optional,
+    Many,
+}
+
+impl Multiplicity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Multiplicity::One => "1",
+            Multiplicity::Optional => "0..1",
+            Multiplicity::Many => "*",
+        }
+    }
+}
+
+struct Association<'a> {
+    target: &'a JPath,
+    multiplicity: Multiplicity,
+    qualifier: Option<&'a JType>,
+}
+
+/// Members of interfaces and annotation types are implicitly static, the
+/// parser already marks nested interfaces, enums and records as static.
+fn is_static_nested(class: &Class, parent: &Class) -> bool {
+    class.modifiers.m_static()
+        || matches!(
+            parent.class_type,
+            ClassType::Interface | ClassType::InterfaceA
+        )
+}
+
+fn is_direct_subtype(class: &Class, of: &JPath) -> bool {
+    class
+        .extends
+        .iter()
+        .chain(class.implements.iter())
+        .flatten()
+        .any(|jtype| resolves_to(jtype, of))
+}
+
+fn resolves_to(jtype: &JType, path: &JPath) -> bool {
+    matches!(
+        jtype,
+        JType::Object {
+            path: TypePath {
+                resolved: TypeResolution::Some(resolved),
+                ..
+            },
+            ..
+        } if resolved == path
+    )
+}
+
+fn is_nested_in(project: &Project, class: &Class, outer: &JPath) -> bool {
+    let mut parent = class.parent_class.as_ref();
+    while let Some(path) = parent {
+        if path == outer {
+            return true;
+        }
+        parent = project
+            .type_map
+            .get(path)
+            .and_then(|class| class.parent_class.as_ref());
+    }
+    false
+}
+
+/// Controls what [`PlantUmlGen`] puts in the diagram. The default shows
+/// everything.
+#[derive(Debug, Clone, Default)]
+pub struct PlantUmlOptions {
+    pub members: MemberFilter,
+    /// Only classes matching one of these globs are shown, every class is
+    /// when empty.
+    pub include: Vec<String>,
+    /// Classes matching one of these globs are hidden.
+    pub exclude: Vec<String>,
+    /// The most fields and methods shown in a class, the rest are replaced
+    /// by a `.. n more ..` separator.
+    pub max_members: Option<usize>,
+    /// Attaches a note with the Javadoc summary to every documented class.
+    pub class_notes: bool,
+    /// Attaches a note listing the Javadoc summary of every documented
+    /// field and method shown.
+    pub member_notes: bool,
+    /// Summaries longer than this many characters are cut short with `...`.
+    pub note_max_len: Option<usize>,
+    /// Wraps notes onto lines of at most this many characters.
+    pub note_width: Option<usize>,
+}
+
+impl PlantUmlOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Globs are matched against the full class path, `com.example.Foo`,
+    /// and the class name, `Foo`. `*` matches any run of characters and `?`
+    /// a single one.
+    fn shows(&self, class: &Class) -> bool {
+        let name = match &class.package {
+            Some(package) => class
+                .class_path
+                .path
+                .trim_start_matches(&package.path)
+                .trim_start_matches('.'),
+            None => class.class_path.path.as_str(),
+        };
+        let matches = |glob: &String| {
+            glob_matches(glob, &class.class_path.path) || glob_matches(glob, name)
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+/// Truncates and wraps a Javadoc summary for a note.
+fn format_note(summary: &str, options: &PlantUmlOptions) -> String {
+    let mut text = summary.to_owned();
+    if let Some(max) = options.note_max_len {
+        if let Some((end, _)) = text.char_indices().nth(max) {
+            text.truncate(end);
+            text.truncate(text.trim_end().len());
+            text.push_str("...");
+        }
+    }
+
+    let Some(width) = options.note_width else {
+        return text;
+    };
+    let mut wrapped = String::new();
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        let len = word.chars().count();
+        if line_len > 0 && line_len + 1 + len > width {
+            wrapped.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            wrapped.push(' ');
+            line_len += 1;
+        }
+        wrapped.push_str(word);
+        line_len += len;
+    }
+    wrapped
+}
+
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // the last `*` seen and the position in text it was tried at
+    let mut star = None;
+    let (mut g, mut t) = (0, 0);
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_g, star_t)) => {
+                    star = Some((star_g, star_t + 1));
+                    g = star_g + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
 pub struct PlantUmlGen<'a, T: std::io::Write> {
     java: &'a Project<'a>,
     out: &'a mut T,
+    options: PlantUmlOptions,
+    pass: Pass,
+    /// How many more members of the current class can be shown.
+    budget: usize,
+    /// How many members of the current class were left out for the budget.
+    omitted: usize,
+    /// The documented members of the current class that were shown, as
+    /// their label and summary.
+    member_notes: Vec<(String, String)>,
 }
 
 impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
-    pub fn new(out: &'a mut T, java: &'a Project<'a>) -> Self {
-        Self { java, out }
+    pub fn new(out: &'a mut T, java: &'a Project<'a>, options: PlantUmlOptions) -> Self {
+        Self {
+            java,
+            out,
+            options,
+            pass: Pass::Classes,
+            budget: usize::MAX,
+            omitted: 0,
+            member_notes: Vec::new(),
+        }
     }
 
     pub fn write(&mut self) -> Result {
-        self.out.write_all(
-            "@startuml
-        skinparam fixCircleLabelOverlapping true 
-        skinparam nodesep 100
-        skinparam ranksep 100
-        skinparam linetype ortho
-        'skinparam linetype polyline
-        ' !pragma layout elk      
-        set separator ::\n\n"
-                .as_bytes(),
-        )?;
-
-        for class in self.java.type_map.values() {
-            self.write_class(class)?;
-        }
+        uml::visit_passes(self.java, self)
+    }
 
-        for package in self.java.packages.keys() {
-            if package.0.path.is_empty() {
-                continue;
-            }
-            self.out.write_all("package ".as_bytes())?;
-            self.write_package_path(&package.0)?;
-            let num = package.0.path.split('.').filter(|v| !v.is_empty()).count();
+    fn write_package(&mut self, package: &JPath) -> Result {
+        self.out.write_all("package ".as_bytes())?;
+        self.write_package_path(package)?;
+        let num = package.path.split('.').filter(|v| !v.is_empty()).count();
 
-            match num % 5 {
-                0 => self.out.write_all(" #FFFFFF {\n}\n".as_bytes())?,
-                1 => self.out.write_all(" #EEEEEE {\n}\n".as_bytes())?,
-                2 => self.out.write_all(" #DDDDDD {\n}\n".as_bytes())?,
-                3 => self.out.write_all(" #CCCCCC {\n}\n".as_bytes())?,
-                4 => self.out.write_all(" #BBBBBB {\n}\n".as_bytes())?,
-                _ => unreachable!(),
-            }
+        match num % 5 {
+            0 => self.out.write_all(" #FFFFFF {\n}\n".as_bytes()),
+            1 => self.out.write_all(" #EEEEEE {\n}\n".as_bytes()),
+            2 => self.out.write_all(" #DDDDDD {\n}\n".as_bytes()),
+            3 => self.out.write_all(" #CCCCCC {\n}\n".as_bytes()),
+            4 => self.out.write_all(" #BBBBBB {\n}\n".as_bytes()),
+            _ => unreachable!(),
         }
+    }
 
-        for class in self.java.type_map.values() {
-            if let Some(extends) = &class.extends{
-                for extends in extends{
-                    self.write_full_class_path(&class.class_path, &class.package)?;
-                    self.out.write_all(" --|> ".as_bytes())?;
-                    self.write_type_path(extends)?;
-                    self.out.write_all(" : extends\n".as_bytes())?;
+    fn write_supertypes(&mut self, class: &Class) -> Result {
+        if let Some(extends) = &class.extends{
+            for extends in extends{
+                if self.is_hidden_type(extends) {
+                    continue;
                 }
+                self.write_full_class_path(&class.class_path, &class.package)?;
+                self.out.write_all(" --|> ".as_bytes())?;
+                self.write_type_path(extends)?;
+                self.out.write_all(" : extends\n".as_bytes())?;
             }
-            if let Some(extends) = &class.implements{
-                for extends in extends{
-                    self.write_full_class_path(&class.class_path, &class.package)?;
-                    self.out.write_all(" ..|> ".as_bytes())?;
-                    self.write_type_path(extends)?;
-                    self.out.write_all(" : implements\n".as_bytes())?;
+        }
+        if let Some(extends) = &class.implements{
+            for extends in extends{
+                if self.is_hidden_type(extends) {
+                    continue;
                 }
+                self.write_full_class_path(&class.class_path, &class.package)?;
+                self.out.write_all(" ..|> ".as_bytes())?;
+                self.write_type_path(extends)?;
+                self.out.write_all(" : implements\n".as_bytes())?;
             }
         }
+        Ok(())
+    }
 
-        self.out.write_all("@enduml".as_bytes())
+    /// Counts a member that passed the filters against `max_members`,
+    /// returns whether it can still be shown.
+    fn take_budget(&mut self) -> bool {
+        if self.budget == 0 {
+            self.omitted += 1;
+            false
+        } else {
+            self.budget -= 1;
+            true
+        }
     }
 
     fn write_type_path(&mut self, jtype: &JType) -> Result{
@@ -102,6 +321,257 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
         }
     }
 
+    /// A class is hidden when it, or any class enclosing it, is marked with
+    /// `UML_HIDE` or left out by the include and exclude globs.
+    fn is_hidden(&self, class: &Class) -> bool {
+        self.java
+            .enclosing_classes(class)
+            .any(|class| class.meta.hidden || !self.options.shows(class))
+    }
+
+    fn is_hidden_type(&self, jtype: &JType) -> bool {
+        match jtype {
+            JType::Object {
+                path:
+                    TypePath {
+                        resolved: TypeResolution::Some(resolved),
+                        ..
+                    },
+                ..
+            } => {
+                self.java.omitted.contains(resolved)
+                    || self
+                        .java
+                        .type_map
+                        .get(resolved)
+                        .is_some_and(|class| self.is_hidden(class))
+            }
+            _ => false,
+        }
+    }
+
+    /// Connects a nested class to the class enclosing it. Static nested
+    /// classes get a solid `+--` line and inner classes, which hold a
+    /// reference to their enclosing instance, a dotted `+..` one.
+    ///
+    /// `INNER_CLASS_LINE_P_C` metadata replaces the connector written
+    /// between parent and child and `INNER_CLASS_LINE_NOTE` adds a note to
+    /// the line.
+    fn write_nesting_line(&mut self, parent: &Class, class: &Class) -> Result {
+        let mut connector = None;
+        let mut notes = Vec::new();
+        for meta in &class.meta.other {
+            match meta {
+                UmlMeta::InnerClassLinePC(line) => connector = Some(*line),
+                UmlMeta::InnerClassNote(note) => notes.push(*note),
+                _ => {}
+            }
+        }
+        let default = if is_static_nested(class, parent) {
+            "+--"
+        } else {
+            "+.."
+        };
+
+        self.write_full_class_path(&parent.class_path, &parent.package)?;
+        self.out.write_all(" ".as_bytes())?;
+        self.out
+            .write_all(connector.unwrap_or(default).trim().as_bytes())?;
+        self.out.write_all(" ".as_bytes())?;
+        self.write_full_class_path(&class.class_path, &class.package)?;
+        self.out.write_all("\n".as_bytes())?;
+
+        for note in notes {
+            self.out.write_all("note on link\n".as_bytes())?;
+            self.out.write_all(note.trim().as_bytes())?;
+            self.out.write_all("\nend note\n".as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes a `permits` line from a sealed type to each permitted subtype.
+    /// Without a `permits` clause the permitted subtypes are the direct
+    /// subtypes declared in the same file. A permitted class that doesn't
+    /// extend or implement the sealed type is drawn in red.
+    fn write_permits(&mut self, class: &Class) -> Result {
+        if !class.modifiers.m_sealed() {
+            return Ok(());
+        }
+
+        // (the type named in the permits clause, the class it resolved to)
+        let permits: Vec<(Option<&JType>, Option<&Class>)> = if let Some(permits) = &class.permits
+        {
+            permits
+                .iter()
+                .map(|permit| {
+                    let subtype = match permit {
+                        JType::Object {
+                            path:
+                                TypePath {
+                                    resolved: TypeResolution::Some(resolved),
+                                    ..
+                                },
+                            ..
+                        } => self.java.type_map.get(resolved),
+                        _ => None,
+                    };
+                    (Some(permit), subtype)
+                })
+                .collect()
+        } else {
+            let file = self.java.files.get(&class.class_path);
+            let mut subtypes: Vec<&Class> = self
+                .java
+                .type_map
+                .values()
+                .filter(|sub| file.is_some() && self.java.files.get(&sub.class_path) == file)
+                .filter(|sub| is_direct_subtype(sub, &class.class_path))
+                .collect();
+            subtypes.sort_by(|a, b| a.class_path.path.cmp(&b.class_path.path));
+            subtypes.into_iter().map(|sub| (None, Some(sub))).collect()
+        };
+
+        for (permit, subtype) in permits {
+            if subtype.is_some_and(|sub| self.is_hidden(sub))
+                || permit.is_some_and(|permit| self.is_hidden_type(permit))
+            {
+                continue;
+            }
+            let is_subtype = subtype.is_none_or(|sub| is_direct_subtype(sub, &class.class_path));
+
+            self.write_full_class_path(&class.class_path, &class.package)?;
+            if is_subtype {
+                self.out.write_all(" ..> ".as_bytes())?;
+            } else {
+                self.out.write_all(" .[#red].> ".as_bytes())?;
+            }
+            match (subtype, permit) {
+                (Some(sub), _) => self.write_full_class_path(&sub.class_path, &sub.package)?,
+                (None, Some(permit)) => self.write_type_path(permit)?,
+                (None, None) => {}
+            }
+            if is_subtype {
+                self.out.write_all(" : permits\n".as_bytes())?;
+            } else {
+                self.out
+                    .write_all(" : permits\\n<color:red>not a subtype</color>\n".as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_associations(&mut self, class: &Class, variable: &Variable) -> Result {
+        let mut associations = Vec::new();
+        self.collect_associations(&variable.jtype, Multiplicity::One, None, &mut associations);
+
+        for association in associations {
+            let Some(target) = self.java.type_map.get(association.target) else {
+                continue;
+            };
+            if self.is_hidden(target) {
+                continue;
+            }
+            self.write_full_class_path(&class.class_path, &class.package)?;
+            if let Some(qualifier) = association.qualifier {
+                self.out.write_all(" [".as_bytes())?;
+                self.visit_type(qualifier)?;
+                self.out.write_all("]".as_bytes())?;
+            }
+            // a field holding one of our own nested classes owns it
+            if is_nested_in(self.java, target, &class.class_path) {
+                self.out.write_all(" *-- \"".as_bytes())?;
+            } else {
+                self.out.write_all(" --> \"".as_bytes())?;
+            }
+            self.out
+                .write_all(association.multiplicity.as_str().as_bytes())?;
+            self.out.write_all("\" ".as_bytes())?;
+            self.write_full_class_path(&target.class_path, &target.package)?;
+            self.out.write_all(" : ".as_bytes())?;
+            self.out.write_all(variable.name.as_bytes())?;
+            self.out.write_all("\n".as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn collect_associations<'b>(
+        &self,
+        jtype: &'b JType,
+        multiplicity: Multiplicity,
+        qualifier: Option<&'b JType>,
+        out: &mut Vec<Association<'b>>,
+    ) {
+        let JType::Object {
+            path,
+            generics,
+            arr,
+        } = jtype
+        else {
+            return;
+        };
+        let multiplicity = if arr.is_some() {
+            Multiplicity::Many
+        } else {
+            multiplicity
+        };
+
+        let type_arg = |index: usize| {
+            generics
+                .as_ref()
+                .and_then(|generics| generics.invoctions.get(index))
+                .and_then(|invoction| match invoction {
+                    GenericInvoctionPart::Type(jtype) => Some(jtype),
+                    GenericInvoctionPart::Wildcard(WildcardBound::Extends(bounds)) => {
+                        bounds.first()
+                    }
+                    GenericInvoctionPart::Wildcard(_) => None,
+                })
+        };
+
+        if let TypeResolution::Some(resolved) = &path.resolved {
+            if self.java.type_map.contains_key(resolved) {
+                out.push(Association {
+                    target: resolved,
+                    multiplicity,
+                    qualifier,
+                });
+                return;
+            }
+        }
+
+        match path.origional.last() {
+            "Collection" | "Iterable" | "List" | "ArrayList" | "LinkedList" | "Vector"
+            | "Stack" | "Set" | "HashSet" | "LinkedHashSet" | "SortedSet" | "NavigableSet"
+            | "TreeSet" | "EnumSet" | "Queue" | "Deque" | "ArrayDeque" | "PriorityQueue" => {
+                if let Some(element) = type_arg(0) {
+                    self.collect_associations(element, Multiplicity::Many, qualifier, out);
+                }
+            }
+            "Optional" => {
+                if let Some(element) = type_arg(0) {
+                    self.collect_associations(
+                        element,
+                        multiplicity.max(Multiplicity::Optional),
+                        qualifier,
+                        out,
+                    );
+                }
+            }
+            "Map" | "HashMap" | "LinkedHashMap" | "SortedMap" | "NavigableMap" | "TreeMap"
+            | "EnumMap" | "Hashtable" | "ConcurrentMap" | "ConcurrentHashMap" => {
+                if let (Some(key), Some(value)) = (type_arg(0), type_arg(1)) {
+                    self.collect_associations(
+                        value,
+                        multiplicity.max(Multiplicity::Optional),
+                        Some(key),
+                        out,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn write_package_path(&mut self, package: &JPath) -> Result {
         for part in package.path.split('.') {
             self.out.write_all(part.as_bytes())?;
@@ -132,26 +602,6 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
         Ok(())
     }
 
-    fn write_class_type_name(&mut self, jtype: &TypePath) -> Result {
-        match &jtype.resolved {
-            TypeResolution::Some(full_path) => {
-                if let Some(some) = self.java.type_map.get(full_path) {
-                    let str = if let Some(package) = &some.package {
-                        jtype.origional.path.trim_end_matches(&package.path)
-                    } else {
-                        &jtype.origional.path
-                    };
-                    self.out.write_all(str.as_bytes())
-                } else {
-                    self.out.write_all(jtype.origional.path.as_bytes())
-                }
-            }
-            TypeResolution::None | TypeResolution::Generic => {
-                self.out.write_all(jtype.origional.path.as_bytes())
-            }
-        }
-    }
-
     fn write_class(&mut self, class: &Class) -> Result {
         self.write_visibility(class.visibility)?;
         let kind = if class.modifiers.m_abstract() {
@@ -162,7 +612,7 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
                 ClassType::Interface => "interface",
                 ClassType::Enum(_enums) => "enum",
                 ClassType::Record => "class",
-                ClassType::InterfaceA => "interface",
+                ClassType::InterfaceA => "annotation",
             }
         };
         self.out.write_all(kind.as_bytes())?;
@@ -192,7 +642,10 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
             self.out.write_all("<<record>>".as_bytes())?;
         }
         if class.modifiers.m_static()
-            && !matches!(class.class_type, ClassType::Enum(_) | ClassType::Interface)
+            && !matches!(
+                class.class_type,
+                ClassType::Enum(_) | ClassType::Interface | ClassType::InterfaceA
+            )
         {
             self.out.write_all("<<static>>".as_bytes())?;
         }
@@ -201,8 +654,18 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
         {
             self.out.write_all("<<final>>".as_bytes())?;
         }
+        if class.modifiers.m_sealed() {
+            self.out.write_all("<<sealed>>".as_bytes())?;
+        }
+        if class.modifiers.m_non_sealed() {
+            self.out.write_all("<<non-sealed>>".as_bytes())?;
+        }
+        if self.java.stubs.contains(&class.class_path) {
+            self.out.write_all("<<stub>>".as_bytes())?;
+        }
 
         self.out.write_all(" {\n".as_bytes())?;
+        self.write_meta_lines(&class.meta)?;
         if let ClassType::Enum(enums) = &class.class_type {
             for name in enums {
                 self.out.write_all("  ".as_bytes())?;
@@ -212,35 +675,106 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
 
             self.out.write_all("  ==\n".as_bytes())?;
         }
+        Ok(())
+    }
+
+    /// Closes the block opened by [`Self::write_class`], the members were
+    /// written in between.
+    fn write_class_end(&mut self, class: &Class) -> Result {
+        self.out.write_all("\n}\n".as_bytes())?;
+
+        self.write_meta_raw_outer(&class.meta)?;
+        let members = &self.options.members;
+        let variables = class
+            .variables
+            .iter()
+            .filter(|v| !v.meta.hidden && members.keeps_variable(class, v));
+        let functions = class
+            .functions
+            .iter()
+            .filter(|f| !f.meta.hidden && members.keeps_function(class, f));
+        let metas: Vec<&Metadata> = variables
+            .map(|v| &v.meta)
+            .chain(functions.map(|f| &f.meta))
+            .collect();
+        for meta in metas {
+            self.write_meta_raw_outer(meta)?;
+        }
+        Ok(())
+    }
 
-        for variable in &class.variables {
-            self.visit_variable(variable)?;
+    /// Writes the notes for [`PlantUmlOptions::class_notes`] and
+    /// [`PlantUmlOptions::member_notes`] after the class block.
+    fn write_notes(&mut self, class: &Class) -> Result {
+        let summary = class.meta.doc.as_ref()
+            .map(|doc| doc.summary.as_str())
+            .filter(|summary| self.options.class_notes && !summary.is_empty());
+        if let Some(summary) = summary {
+            self.out.write_all("note top of ".as_bytes())?;
+            self.write_full_class_path(&class.class_path, &class.package)?;
+            self.out.write_all("\n".as_bytes())?;
+            self.out.write_all(format_note(summary, &self.options).as_bytes())?;
+            self.out.write_all("\nend note\n".as_bytes())?;
         }
 
-        for function in &class.functions {
-            self.visit_function(function)?;
+        let members = std::mem::take(&mut self.member_notes);
+        if members.is_empty() {
+            return Ok(());
         }
+        self.out.write_all("note bottom of ".as_bytes())?;
+        self.write_full_class_path(&class.class_path, &class.package)?;
+        self.out.write_all("\n".as_bytes())?;
+        for (label, summary) in members {
+            self.out.write_all("**".as_bytes())?;
+            self.out.write_all(label.as_bytes())?;
+            self.out.write_all("**: ".as_bytes())?;
+            self.out.write_all(format_note(&summary, &self.options).as_bytes())?;
+            self.out.write_all("\n".as_bytes())?;
+        }
+        self.out.write_all("end note\n".as_bytes())
+    }
 
-        self.out.write_all("\n}\n".as_bytes())?;
+    /// Remembers the summary of a member that was just written.
+    fn push_member_note(&mut self, label: String, meta: &Metadata) {
+        if !self.options.member_notes {
+            return;
+        }
+        if let Some(doc) = meta.doc.as_ref().filter(|doc| !doc.summary.is_empty()) {
+            self.member_notes.push((label, doc.summary.clone()));
+        }
+    }
+
+    fn write_meta_lines(&mut self, meta: &Metadata) -> Result {
+        for meta in &meta.other {
+            if let UmlMeta::Line(line) = meta {
+                self.out.write_all("  ".as_bytes())?;
+                self.out.write_all(line.trim().as_bytes())?;
+                self.out.write_all("\n".as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_meta_raw_outer(&mut self, meta: &Metadata) -> Result {
+        for meta in &meta.other {
+            if let UmlMeta::RawOuter(raw) = meta {
+                self.out.write_all(raw.trim().as_bytes())?;
+                self.out.write_all("\n".as_bytes())?;
+            }
+        }
         Ok(())
     }
 
     fn write_visibility(&mut self, vis: Visibility) -> Result {
-        let buf = match vis {
-            Visibility::Public => "+",
-            Visibility::Protected => "#",
-            Visibility::Private => "-",
-            Visibility::None => "~",
-        };
-        self.out.write_all(buf.as_bytes())
+        self.out.write_all(uml::visibility(vis).as_bytes())
     }
 
-    fn visit_variable(&mut self, variable: &Variable) -> Result {
+    fn write_variable(&mut self, class: &Class, variable: &Variable) -> Result {
         self.out.write_all("  ".as_bytes())?;
         if variable.modifiers.m_static() {
             self.out.write_all("{static} ".as_bytes())?;
         }
-        self.write_visibility(variable.visibility)?;
+        self.write_visibility(class.member_visibility(variable.visibility))?;
         if variable.modifiers.m_final() {
             self.out.write_all("final ".as_bytes())?;
         }
@@ -251,7 +785,7 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
         self.out.write_all("\n".as_bytes())
     }
 
-    fn visit_function(&mut self, function: &Function) -> Result {
+    fn write_function(&mut self, class: &Class, function: &Function) -> Result {
         self.out.write_all("  ".as_bytes())?;
 
         if function.modifiers.m_static() {
@@ -261,7 +795,7 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
             self.out.write_all("{abstract} ".as_bytes())?;
         }
 
-        self.write_visibility(function.visibility)?;
+        self.write_visibility(class.member_visibility(function.visibility))?;
 
         if function.modifiers.m_final() {
             self.out.write_all("final ".as_bytes())?;
@@ -289,7 +823,12 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
         }
 
         self.out.write_all("(".as_bytes())?;
-        for (index, param) in function.parameters.iter().enumerate() {
+        let parameters: Vec<_> = function
+            .parameters
+            .iter()
+            .filter(|param| !param.meta.hidden)
+            .collect();
+        for (index, param) in parameters.iter().enumerate() {
             if param.modifiers.m_final() {
                 self.out.write_all("final ".as_bytes())?;
             }
@@ -301,7 +840,7 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
             }
             self.out.write_all(param.name.as_bytes())?;
 
-            if index != function.parameters.len() - 1 {
+            if index != parameters.len() - 1 {
                 self.out.write_all(", ".as_bytes())?;
             }
         }
@@ -312,6 +851,14 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
                 self.out.write_all(": ".as_bytes())?;
                 self.visit_type(ret)?;
             }
+            FunctionKind::AnnotationElement(ret, default) => {
+                self.out.write_all(": ".as_bytes())?;
+                self.visit_type(ret)?;
+                if let Some(default) = default {
+                    self.out.write_all(" = ".as_bytes())?;
+                    self.write_element_value(default)?;
+                }
+            }
             FunctionKind::Constructor | FunctionKind::CompactConstructor => {}
         }
 
@@ -322,85 +869,502 @@ impl<'a, T: std::io::Write> PlantUmlGen<'a, T> {
         self.out.write_all("\n".as_bytes())
     }
 
-    fn visit_type(&mut self, jtype: &JType) -> Result {
-        match jtype {
-            JType::Primitive(prim) => self.write_primitive(prim),
-            JType::PrimitiveArr(prim, arr) => {
-                self.write_primitive(prim)?;
-                for _ in 0..arr.get() {
-                    self.out.write_all("[]".as_bytes())?;
-                }
-                Ok(())
+    fn write_element_value(&mut self, value: &ElementValue) -> Result {
+        match value {
+            ElementValue::Literal(text) | ElementValue::Expression(text) => {
+                self.out.write_all(text.as_bytes())
             }
-            JType::Object {
-                path,
-                generics,
-                arr,
-            } => {
-                self.write_class_type_name(path)?;
-                if let Some(gen) = generics {
-                    self.out.write_all("<".as_bytes())?;
-                    for (index, inv) in gen.invoctions.iter().enumerate() {
-                        match inv {
-                            GenericInvoctionPart::Type(jtype) => self.visit_type(jtype)?,
-                            GenericInvoctionPart::Wildcard(kind) => {
-                                self.out.write_all("?".as_bytes())?;
-
-                                match kind {
-                                    WildcardBound::None => {}
-                                    WildcardBound::Extends(_) => {
-                                        self.out.write_all(" extends ".as_bytes())?;
-                                    }
-                                    WildcardBound::Super(_) => {
-                                        self.out.write_all(" super ".as_bytes())?;
-                                    }
-                                }
-                                match kind {
-                                    WildcardBound::None => {}
-                                    WildcardBound::Extends(types) | WildcardBound::Super(types) => {
-                                        self.write_type_list(types)?;
-                                    }
-                                }
-                            }
-                        }
-                        if index != gen.invoctions.len() - 1 {
-                            self.out.write_all(", ".as_bytes())?;
-                        }
+            ElementValue::Class(jtype) => {
+                self.visit_type(jtype)?;
+                self.out.write_all(".class".as_bytes())
+            }
+            ElementValue::EnumConstant(path) => self.out.write_all(path.path.as_bytes()),
+            ElementValue::Annotation(annotation) => {
+                self.out.write_all("@".as_bytes())?;
+                self.out.write_all(annotation.name.path.as_bytes())?;
+                if annotation.elements.is_empty() {
+                    return Ok(());
+                }
+                self.out.write_all("(".as_bytes())?;
+                for (index, (name, value)) in annotation.elements.iter().enumerate() {
+                    if index != 0 {
+                        self.out.write_all(", ".as_bytes())?;
                     }
-                    self.out.write_all(">".as_bytes())?;
+                    self.out.write_all(name.as_bytes())?;
+                    self.out.write_all(" = ".as_bytes())?;
+                    self.write_element_value(value)?;
                 }
-
-                for _ in 0..arr.map(|v| v.get()).unwrap_or(0) {
-                    self.out.write_all("[]".as_bytes())?;
+                self.out.write_all(")".as_bytes())
+            }
+            ElementValue::Array(values) => {
+                self.out.write_all("{".as_bytes())?;
+                for (index, value) in values.iter().enumerate() {
+                    if index != 0 {
+                        self.out.write_all(", ".as_bytes())?;
+                    }
+                    self.write_element_value(value)?;
                 }
-                Ok(())
+                self.out.write_all("}".as_bytes())
             }
         }
     }
 
+    fn visit_type(&mut self, jtype: &JType) -> Result {
+        let java = self.java;
+        let mut str = String::new();
+        TYPES.write(jtype, &|path| class_type_name(java, path), &mut str);
+        self.out.write_all(str.as_bytes())
+    }
+
     fn write_type_list(&mut self, list: &[JType]) -> Result {
-        for (index, jtype) in list.iter().enumerate() {
-            self.visit_type(jtype)?;
-            if index != list.len() - 1 {
-                self.out.write_all(", ".as_bytes())?;
+        let java = self.java;
+        let mut str = String::new();
+        TYPES.write_list(list, &|path| class_type_name(java, path), &mut str);
+        self.out.write_all(str.as_bytes())
+    }
+}
+
+impl<'a, T: std::io::Write> uml::PassVisitor for PlantUmlGen<'a, T> {
+    fn set_pass(&mut self, pass: Pass) {
+        self.pass = pass;
+    }
+}
+
+impl<'a, T: std::io::Write> Visitor for PlantUmlGen<'a, T> {
+    type Ok = ();
+    type Err = std::io::Error;
+
+    fn visit_start(&mut self) -> Result {
+        if self.pass != Pass::Classes {
+            return Ok(());
+        }
+        self.out.write_all(
+            "@startuml
+        skinparam fixCircleLabelOverlapping true 
+        skinparam nodesep 100
+        skinparam ranksep 100
+        skinparam linetype ortho
+        'skinparam linetype polyline
+        ' !pragma layout elk      
+        set separator ::\n\n"
+                .as_bytes(),
+        )
+    }
+
+    fn visit_package_start(&mut self, package: &JPath) -> Result {
+        if self.pass != Pass::Relations || package.path.is_empty() {
+            return Ok(());
+        }
+        self.write_package(package)
+    }
+
+    fn visit_class(&mut self, class: &Class) -> Result {
+        self.budget = self.options.max_members.unwrap_or(usize::MAX);
+        self.omitted = 0;
+        self.member_notes.clear();
+        if self.is_hidden(class) {
+            return Ok(());
+        }
+        match self.pass {
+            Pass::Classes => self.write_class(class),
+            Pass::Relations => {
+                self.write_supertypes(class)?;
+                self.write_permits(class)
             }
         }
-        Ok(())
     }
 
-    fn write_primitive(&mut self, prim: &Primitive) -> Result {
-        use crate::java::ast::types::Primitive::*;
-        let str = match prim {
-            Byte => "byte",
-            Short => "short",
-            Int => "int",
-            Long => "long",
-            Float => "float",
-            Double => "double",
-            Char => "char",
-            Void => "void",
-            Boolean => "boolean",
-        };
-        self.out.write_all(str.as_bytes())
+    fn visit_field(&mut self, class: &Class, field: &Variable) -> Result {
+        if field.meta.hidden || self.is_hidden(class) {
+            return Ok(());
+        }
+        // the member filters only decide what is listed in the class, the
+        // relations of filtered fields are still drawn
+        let listed = self.options.members.keeps_variable(class, field);
+        match self.pass {
+            Pass::Classes => {
+                if !listed || !self.take_budget() {
+                    return Ok(());
+                }
+                self.write_variable(class, field)?;
+                self.push_member_note(field.name.to_owned(), &field.meta);
+                self.write_meta_lines(&field.meta)
+            }
+            // fields come before the methods, so the budget runs out on
+            // the same fields as in the first pass and their relations are
+            // left out with them
+            Pass::Relations => {
+                if listed && !self.take_budget() {
+                    return Ok(());
+                }
+                self.write_associations(class, field)
+            }
+        }
+    }
+
+    fn visit_function(&mut self, class: &Class, function: &Function) -> Result {
+        if function.meta.hidden
+            || self.pass != Pass::Classes
+            || !self.options.members.keeps_function(class, function)
+            || !self.take_budget()
+            || self.is_hidden(class)
+        {
+            return Ok(());
+        }
+        self.write_function(class, function)?;
+        self.push_member_note(format!("{}()", function.name), &function.meta);
+        self.write_meta_lines(&function.meta)
+    }
+
+    fn visit_class_end(&mut self, class: &Class) -> Result {
+        if self.pass != Pass::Classes || self.is_hidden(class) {
+            return Ok(());
+        }
+        if self.omitted > 0 {
+            self.out
+                .write_all(format!("  .. {} more ..\n", self.omitted).as_bytes())?;
+        }
+        self.write_class_end(class)?;
+        self.write_notes(class)
+    }
+
+    fn visit_nested_class(&mut self, parent: &Class, nested: &Class) -> Result {
+        if self.pass != Pass::Relations || self.is_hidden(nested) {
+            return Ok(());
+        }
+        self.write_nesting_line(parent, nested)
     }
+
+    fn visit_end(&mut self) -> Result {
+        if self.pass != Pass::Relations {
+            return Ok(());
+        }
+        self.out.write_all("@enduml".as_bytes())
+    }
+}
+
+#[cfg(test)]
+fn render(files: &[(&str, &str)], options: PlantUmlOptions) -> String {
+    crate::render(files, |out, project| PlantUmlGen::new(out, project, options).write())
+}
+
+#[test]
+fn test_associations() {
+    let files = [
+        (
+            "Owner.java",
+            r#"
+        import java.util.*;
+
+        public class Owner {
+            Item single;
+            List<Item> many;
+            Item[] array;
+            Optional<Item> maybe;
+            Map<String, Item> byName;
+            Part part;
+
+            class Part {}
+        }
+        "#,
+        ),
+        ("Item.java", "public class Item {}"),
+    ];
+
+    let out = render(&files, PlantUmlOptions::new());
+
+    assert!(out.contains("Owner --> \"1\" Item : single\n"));
+    assert!(out.contains("Owner --> \"*\" Item : many\n"));
+    assert!(out.contains("Owner --> \"*\" Item : array\n"));
+    assert!(out.contains("Owner --> \"0..1\" Item : maybe\n"));
+    assert!(out.contains("Owner [String] --> \"0..1\" Item : byName\n"));
+    assert!(out.contains("Owner *-- \"1\" Owner.Part : part\n"));
+}
+
+#[test]
+fn test_filtered_associations() {
+    let files = [
+        ("A.java", "public class A { public int shown; private B secret; public B exposed; }"),
+        ("B.java", "public class B {}"),
+    ];
+
+    let mut options = PlantUmlOptions::new();
+    options.members.min_visibility = Visibility::Public;
+    options.max_members = Some(1);
+
+    let out = render(&files, options);
+
+    assert!(out.contains("+class A  {\n  +shown: int\n  .. 1 more ..\n"));
+    assert!(!out.contains("secret: B"));
+    assert!(out.contains("A --> \"1\" B : secret\n"));
+    // but not those of fields cut by the member cap
+    assert!(!out.contains("exposed"));
+}
+
+#[test]
+fn test_nesting_lines() {
+    let files = [
+        (
+            "a/A.java",
+            r#"
+        package a;
+
+        public class A {
+            static class Nested {}
+            class Inner {}
+            interface Callback {}
+            enum Mode { ON }
+            record Pair(int a, int b) {}
+        }
+        "#,
+        ),
+        (
+            "a/I.java",
+            r#"
+        package a;
+
+        public interface I {
+            class Impl {}
+            @interface Tag {
+                class Value {}
+            }
+        }
+        "#,
+        ),
+    ];
+
+    let out = render(&files, PlantUmlOptions::new());
+
+    // only inner classes hold on to their enclosing instance
+    assert!(out.contains("a::A +.. a::A.Inner\n"));
+    assert!(out.contains("a::A +-- a::A.Nested\n"));
+    // nested interfaces, enums and records are implicitly static
+    assert!(out.contains("a::A +-- a::A.Callback\n"));
+    assert!(out.contains("a::A +-- a::A.Mode\n"));
+    assert!(out.contains("a::A +-- a::A.Pair\n"));
+    // and so are the member classes of interfaces and annotation types
+    assert!(out.contains("a::I +-- a::I.Impl\n"));
+    assert!(out.contains("a::I +-- a::I.Tag\n"));
+    assert!(out.contains("a::I.Tag +-- a::I.Tag.Value\n"));
+}
+
+#[test]
+fn test_uml_meta() {
+    assert!(matches!(UmlMeta::parse("HIDE"), UmlMeta::Hide));
+    assert!(matches!(UmlMeta::parse("UML_HIDE "), UmlMeta::Hide));
+    assert!(matches!(UmlMeta::parse("UML_LINE a"), UmlMeta::Line("a")));
+    assert!(matches!(UmlMeta::parse("RAW_OUTER a b"), UmlMeta::RawOuter("a b")));
+    assert!(matches!(UmlMeta::parse("HIDE me"), UmlMeta::Invalid("HIDE me")));
+
+    let files = [
+        (
+            "a/A.java",
+            r#"
+        package a;
+
+        /*UML_RAW_OUTER note "outer" as N1*/
+        public class A {
+            /*UML_LINE -- fields --*/
+            public int shown;
+            /*UML_HIDE*/
+            public int secret;
+            /*UML_HIDE*/
+            public void hidden() {}
+            public void call(int kept, /*UML_HIDE*/ int dropped) {}
+
+            /*UML_INNER_CLASS_LINE_P_C +-->*/
+            /*UML_INNER_CLASS_LINE_NOTE owned*/
+            public static class Inner {}
+
+            /*UML_HIDE*/
+            public static class Gone {
+                public static class Nested {}
+            }
+        }
+        "#,
+        ),
+    ];
+
+    let out = render(&files, PlantUmlOptions::new());
+
+    // UML_LINE goes after the member it's attached to
+    assert!(out.contains(
+        "+class a::A  {\n  +shown: int\n  -- fields --\n  +call(int kept): void\n\n}\nnote \"outer\" as N1\n"
+    ));
+    assert!(!out.contains("secret"));
+    assert!(!out.contains("hidden"));
+    assert!(!out.contains("dropped"));
+    assert!(!out.contains("Gone"));
+    assert!(!out.contains("Nested"));
+    assert!(out.contains("a::A +--> a::A.Inner\nnote on link\nowned\nend note\n"));
+}
+
+#[test]
+fn test_permits() {
+    let files = [
+        (
+            "s/Shape.java",
+            r#"
+        package s;
+
+        public sealed interface Shape permits Circle, Other {}
+        "#,
+        ),
+        ("s/Circle.java", "package s; public non-sealed class Circle implements Shape {}"),
+        ("s/Other.java", "package s; public final class Other {}"),
+        (
+            "s/Expr.java",
+            r#"
+        package s;
+
+        public sealed class Expr {}
+        final class Num extends Expr {}
+        final class Neg extends Expr {}
+        "#,
+        ),
+    ];
+
+    let out = render(&files, PlantUmlOptions::new());
+
+    assert!(out.contains("+interface s::Shape <<sealed>> {\n"));
+    assert!(out.contains("+class s::Circle <<non-sealed>> {\n"));
+    assert!(out.contains("+class s::Expr <<sealed>> {\n"));
+    // explicit permits, one of them not implementing the sealed type
+    assert!(out.contains("s::Shape ..> s::Circle : permits\n"));
+    assert!(out.contains(
+        "s::Shape .[#red].> s::Other : permits\\n<color:red>not a subtype</color>\n"
+    ));
+    // without a permits clause the subtypes in the same file are permitted
+    assert!(out.contains("s::Expr ..> s::Neg : permits\ns::Expr ..> s::Num : permits\n"));
+}
+
+#[test]
+fn test_interface_member_visibility() {
+    let files = [
+        ("I.java", "public interface I { int LIMIT = 1; void run(); private void helper() {} }"),
+        ("A.java", "public @interface A { String value(); }"),
+    ];
+
+    let mut options = PlantUmlOptions::new();
+    options.members.min_visibility = Visibility::Public;
+
+    let out = render(&files, options);
+
+    // shown as public, the same visibility the member filters go by
+    assert!(out.contains("+interface I  {\n  +LIMIT: int\n  +run(): void\n"));
+    assert!(out.contains("+annotation A  {\n  +value(): String\n"));
+    assert!(!out.contains("helper"));
+}
+
+#[test]
+fn test_options() {
+    let files = [
+        (
+            "a/Person.java",
+            r#"
+        package a;
+
+        public class Person {
+            private String name;
+            static int count;
+            protected int age;
+            public Person() {}
+            public String getName() { return name; }
+            public void setName(String name) {}
+            public boolean isAdult() { return true; }
+            public void greet() {}
+            public void wave() {}
+        }
+        "#,
+        ),
+        ("a/PersonTest.java", "package a; public class PersonTest {}"),
+        ("b/Other.java", "package b; public class Other {}"),
+    ];
+
+    let mut options = PlantUmlOptions::new();
+    options.members.min_visibility = Visibility::Protected;
+    options.members.constructors = false;
+    options.members.accessors = false;
+    options.members.statics = false;
+    options.include.push("a.*".into());
+    options.exclude.push("*Test".into());
+    options.max_members = Some(2);
+
+    let out = render(&files, options);
+
+    assert!(out.contains("+class a::Person  {\n  #age: int\n  +greet(): void\n  .. 1 more ..\n"));
+    assert!(!out.contains("name"));
+    assert!(!out.contains("count"));
+    assert!(!out.contains("Person()"));
+    assert!(!out.contains("isAdult"));
+    assert!(!out.contains("PersonTest"));
+    assert!(!out.contains("Other"));
+}
+
+#[test]
+fn test_notes() {
+    let files = [
+        (
+            "a/Shop.java",
+            r#"
+        package a;
+
+        /** Sells things to people who want them. More details follow. */
+        public class Shop {
+            /** The stock. */
+            int stock;
+            int undocumented;
+            /** Sells one thing. */
+            public void sell() {}
+            /** Hidden. */
+            /*UML_HIDE*/
+            public void secret() {}
+        }
+
+        /** Never drawn. */
+        /*UML_HIDE*/
+        class Hidden {}
+        "#,
+        ),
+    ];
+
+    let mut options = PlantUmlOptions::new();
+    options.class_notes = true;
+    options.member_notes = true;
+    options.note_max_len = Some(30);
+    options.note_width = Some(16);
+
+    let out = render(&files, options);
+
+    assert!(out.contains("note top of a::Shop\nSells things to\npeople who\nwan...\nend note\n"));
+    assert!(out.contains("note bottom of a::Shop\n**stock**: The stock.\n**sell()**: Sells one thing.\nend note\n"));
+    assert!(!out.contains("Hidden"));
+    assert!(!out.contains("Never drawn"));
+
+    assert!(!render(&files, PlantUmlOptions::new()).contains("note"));
+}
+
+#[test]
+fn test_annotation_type() {
+    let files = [
+        (
+            "a/Route.java",
+            r#"
+        package a;
+
+        public @interface Route {
+            String value() default "/";
+            int priority();
+            Method[] methods() default {Method.GET, Method.POST};
+        }
+        "#,
+        ),
+    ];
+
+    let out = render(&files, PlantUmlOptions::new());
+
+    assert!(out.contains("+annotation a::Route  {\n"));
+    assert!(out.contains("  +value(): String = \"/\"\n"));
+    assert!(out.contains("  +priority(): int\n"));
+    assert!(out.contains("  +methods(): Method[] = {Method.GET, Method.POST}\n"));
 }
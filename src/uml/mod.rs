@@ -0,0 +1,131 @@
+//! Notation shared by the diagram backends.
+
+use crate::java::{
+    ast::{
+        class::{Class, ClassType},
+        generics::{GenericInvoctionPart, WildcardBound},
+        types::{JType, TypePath},
+        Visibility,
+    },
+    project::{Project, Visitor},
+};
+
+/// Which of the two walks over the project a backend is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Declares the packages and classes.
+    Classes,
+    /// Draws the relations between the classes.
+    Relations,
+}
+
+/// A backend that writes its diagram in two [`Pass`]es.
+pub trait PassVisitor: Visitor {
+    fn set_pass(&mut self, pass: Pass);
+}
+
+/// Walks the project once for the classes and once more for the relations
+/// between them, so no relation refers to a class that wasn't declared yet.
+pub fn visit_passes<V: PassVisitor>(java: &Project, visitor: &mut V) -> Result<V::Ok, V::Err> {
+    visitor.set_pass(Pass::Classes);
+    java.visit(visitor)?;
+    visitor.set_pass(Pass::Relations);
+    java.visit(visitor)
+}
+
+/// The stereotype telling what kind of type a class is, `None` for a
+/// regular class.
+pub fn stereotype(class: &Class) -> Option<&'static str> {
+    match &class.class_type {
+        ClassType::Interface => Some("interface"),
+        ClassType::InterfaceA => Some("annotation"),
+        ClassType::Enum(_) => Some("enumeration"),
+        ClassType::Record => Some("record"),
+        ClassType::Class if class.modifiers.m_abstract() => Some("abstract"),
+        ClassType::Class => None,
+    }
+}
+
+/// The UML symbol for a visibility, `~` standing for package private.
+pub fn visibility(vis: Visibility) -> &'static str {
+    match vis {
+        Visibility::Public => "+",
+        Visibility::Protected => "#",
+        Visibility::Private => "-",
+        Visibility::None => "~",
+    }
+}
+
+/// How a backend spells types. Types are written the way Java does apart
+/// from the brackets around type arguments and the separators, which each
+/// diagram syntax restricts differently.
+#[derive(Debug, Clone, Copy)]
+pub struct TypeFormat {
+    /// Written before and after the type arguments.
+    pub generics: (&'static str, &'static str),
+    /// Written between type arguments.
+    pub arguments: &'static str,
+    /// Written between the types of a list, like the bounds of a wildcard.
+    pub list: &'static str,
+}
+
+impl TypeFormat {
+    /// Appends `jtype` to `out`, with the names of classes given by `name`.
+    pub fn write(&self, jtype: &JType, name: &dyn Fn(&TypePath) -> &str, out: &mut String) {
+        match jtype {
+            JType::Primitive(prim) => out.push_str(prim.name()),
+            JType::PrimitiveArr(prim, arr) => {
+                out.push_str(prim.name());
+                for _ in 0..arr.get() {
+                    out.push_str("[]");
+                }
+            }
+            JType::Object {
+                path,
+                generics,
+                arr,
+            } => {
+                out.push_str(name(path));
+                if let Some(gen) = generics {
+                    out.push_str(self.generics.0);
+                    for (index, inv) in gen.invoctions.iter().enumerate() {
+                        match inv {
+                            GenericInvoctionPart::Type(jtype) => self.write(jtype, name, out),
+                            GenericInvoctionPart::Wildcard(WildcardBound::None) => out.push('?'),
+                            GenericInvoctionPart::Wildcard(WildcardBound::Extends(types)) => {
+                                out.push_str("? extends ");
+                                self.write_list(types, name, out);
+                            }
+                            GenericInvoctionPart::Wildcard(WildcardBound::Super(types)) => {
+                                out.push_str("? super ");
+                                self.write_list(types, name, out);
+                            }
+                        }
+                        if index != gen.invoctions.len() - 1 {
+                            out.push_str(self.arguments);
+                        }
+                    }
+                    out.push_str(self.generics.1);
+                }
+                for _ in 0..arr.map(|v| v.get()).unwrap_or(0) {
+                    out.push_str("[]");
+                }
+            }
+        }
+    }
+
+    /// Appends `list` to `out`, separated by [`Self::list`].
+    pub fn write_list(&self, list: &[JType], name: &dyn Fn(&TypePath) -> &str, out: &mut String) {
+        for (index, jtype) in list.iter().enumerate() {
+            self.write(jtype, name, out);
+            if index != list.len() - 1 {
+                out.push_str(self.list);
+            }
+        }
+    }
+}
+
+/// The name a type was written with in the source.
+pub fn written_name(path: &TypePath) -> &str {
+    &path.origional.path
+}